@@ -0,0 +1,31 @@
+/* Generated by `cargo run --release header`; don't edit by hand. */
+
+#ifndef AOC2021_H
+#define AOC2021_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#define AOC_ANSWER_LEN 64
+
+#define AOC_OK 0
+#define AOC_NO_SUCH_DAY 1
+#define AOC_NULL_POINTER 2
+#define AOC_INVALID_UTF8 3
+#define AOC_SOLVER_PANICKED 4
+#define AOC_ANSWER_TOO_LONG 5
+
+typedef struct {
+    char part1[AOC_ANSWER_LEN];
+    char part2[AOC_ANSWER_LEN];
+    double parse_ms;
+    double part1_ms;
+    double part2_ms;
+} AocSolution;
+
+int32_t aoc_solve(uint32_t day, const uint8_t *input_ptr, size_t input_len,
+                  AocSolution *out);
+
+const char *aoc_status_message(int32_t status);
+
+#endif
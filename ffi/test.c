@@ -0,0 +1,43 @@
+#include <stdio.h>
+#include <string.h>
+
+#include "aoc2021.h"
+
+static int failures = 0;
+
+static void check(int condition, const char *description) {
+    if (!condition) {
+        printf("FAILED: %s\n", description);
+        failures++;
+    }
+}
+
+int main(void) {
+    AocSolution solution;
+    const char *depths = "199\n200\n208\n210\n200\n207\n240\n269\n260\n263\n";
+    int32_t status = aoc_solve(1, (const uint8_t *)depths, strlen(depths), &solution);
+    check(status == AOC_OK, "day 1 example solves");
+    check(strcmp(solution.part1, "7") == 0, "day 1 part 1 answer");
+    check(strcmp(solution.part2, "5") == 0, "day 1 part 2 answer");
+
+    const char *target = "target area: x=20..30, y=-10..-5";
+    status = aoc_solve(17, (const uint8_t *)target, strlen(target), &solution);
+    check(status == AOC_OK, "day 17 example solves");
+    check(strcmp(solution.part1, "45") == 0, "day 17 part 1 answer");
+    check(strcmp(solution.part2, "112") == 0, "day 17 part 2 answer");
+
+    const char *garbage = "garbage";
+    status = aoc_solve(17, (const uint8_t *)garbage, strlen(garbage), &solution);
+    check(status == AOC_SOLVER_PANICKED, "malformed input is reported");
+    printf("malformed input: %s\n", aoc_status_message(status));
+
+    check(aoc_solve(26, NULL, 0, &solution) == AOC_NO_SUCH_DAY, "unknown day is reported");
+    check(aoc_solve(1, NULL, 4, &solution) == AOC_NULL_POINTER, "null input is reported");
+    check(aoc_solve(1, (const uint8_t *)"1", 1, NULL) == AOC_NULL_POINTER, "null output is reported");
+
+    const uint8_t invalid[] = {0xff, 0xfe};
+    check(aoc_solve(1, invalid, 2, &solution) == AOC_INVALID_UTF8, "invalid UTF-8 is reported");
+
+    printf(failures ? "%d checks failed\n" : "all checks passed\n", failures);
+    return failures != 0;
+}
@@ -0,0 +1,217 @@
+use crate::day::{Solution, Solver};
+use std::{
+    any::Any,
+    cell::Cell,
+    fs, io,
+    panic::{self, AssertUnwindSafe},
+    path::Path,
+    sync::Once,
+};
+
+pub enum Outcome {
+    Solved(Solution, Option<Verdict>),
+    Panicked(String),
+}
+
+pub enum Verdict {
+    Correct,
+    Wrong(String),
+}
+
+impl Outcome {
+    fn status(&self) -> &'static str {
+        match self {
+            Self::Solved(_, Some(Verdict::Correct)) => "ok",
+            Self::Solved(_, Some(Verdict::Wrong(_))) => "WRONG",
+            Self::Solved(_, None) => "?",
+            Self::Panicked(_) => "PANIC",
+        }
+    }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => payload
+            .downcast_ref::<&str>()
+            .map_or_else(|| "<unknown panic>".to_string(), |s| s.to_string()),
+    }
+}
+
+fn check(solution: &Solution, answers: &str) -> Verdict {
+    let mismatches = answers
+        .lines()
+        .map(str::trim)
+        .zip([&solution.part1, &solution.part2])
+        .zip(["part1", "part2"])
+        .filter(|((expected, answer), _)| expected != answer)
+        .map(|((expected, _), part)| format!("{} expected {}", part, expected))
+        .collect::<Vec<_>>();
+    if mismatches.is_empty() {
+        Verdict::Correct
+    } else {
+        Verdict::Wrong(mismatches.join(", "))
+    }
+}
+
+thread_local! {
+    /// Whether this thread is inside `evaluate`, whose panics are reported in
+    /// the outcome instead.
+    static SILENCED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Wrap the panic hook, once for the whole process, in one that keeps quiet
+/// on threads inside `evaluate` and defers to the old hook everywhere else.
+fn silence_caught_panics() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !SILENCED.with(Cell::get) {
+                hook(info);
+            }
+        }));
+    });
+}
+
+/// Run a solver against an input, checking it against the answers (one line
+/// per part) if they're given. Panics inside the solver are caught and
+/// reported in the outcome rather than printed.
+pub fn evaluate(solver: Solver, input: &str, answers: Option<&str>) -> Outcome {
+    silence_caught_panics();
+    let was_silenced = SILENCED.with(|silenced| silenced.replace(true));
+    let result = panic::catch_unwind(AssertUnwindSafe(|| solver(input)));
+    SILENCED.with(|silenced| silenced.set(was_silenced));
+    match result {
+        Ok(solution) => {
+            let verdict = answers.map(|answers| check(&solution, answers));
+            Outcome::Solved(solution, verdict)
+        }
+        Err(payload) => Outcome::Panicked(panic_message(payload)),
+    }
+}
+
+/// Run every day on every input under `dir`, which should be laid out as
+/// `dir/<name>/day<N>.in` with optional expected answers in `day<N>.ans`.
+pub fn run(dir: &Path, solvers: &[(usize, Solver)]) -> io::Result<()> {
+    let mut names = fs::read_dir(dir)?
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect::<Vec<_>>();
+    names.sort();
+    println!("corpus: {} ({} inputs)", dir.display(), names.len());
+
+    let name_width = names.iter().map(String::len).max().unwrap_or(0).max(5);
+    let mut summary = Vec::with_capacity(solvers.len());
+    for &(day, solver) in solvers {
+        let mut rows = Vec::new();
+        for name in names.iter() {
+            let path = dir.join(name).join(format!("day{}", day));
+            if let Ok(input) = fs::read_to_string(path.with_extension("in")) {
+                let answers = fs::read_to_string(path.with_extension("ans")).ok();
+                rows.push((name, evaluate(solver, &input, answers.as_deref())));
+            }
+        }
+        if rows.is_empty() {
+            continue;
+        }
+        print_day(day, name_width, &rows);
+        summary.push((day, rows));
+    }
+
+    print_summary(&names, name_width, &summary);
+    Ok(())
+}
+
+fn print_day(day: usize, name_width: usize, rows: &[(&String, Outcome)]) {
+    let answer_width = |part: fn(&Solution) -> &String| {
+        rows.iter()
+            .filter_map(|(_, outcome)| match outcome {
+                Outcome::Solved(solution, _) => Some(part(solution).len()),
+                Outcome::Panicked(_) => None,
+            })
+            .max()
+            .unwrap_or(0)
+            .max(5)
+    };
+    let part1_width = answer_width(|solution| &solution.part1);
+    let part2_width = answer_width(|solution| &solution.part2);
+    println!();
+    println!(
+        "day{:02}:{:w0$}  {:w1$}  {:w2$}  {:>10}  check",
+        day,
+        "",
+        "part1",
+        "part2",
+        "time",
+        w0 = name_width - 4,
+        w1 = part1_width,
+        w2 = part2_width,
+    );
+    for (name, outcome) in rows.iter() {
+        match outcome {
+            Outcome::Solved(solution, verdict) => println!(
+                "  {:w0$}  {:w1$}  {:w2$}  {:>8.3}ms  {}{}",
+                name,
+                solution.part1,
+                solution.part2,
+                1000.0 * solution.total_time().as_secs_f32(),
+                outcome.status(),
+                match verdict {
+                    Some(Verdict::Wrong(mismatches)) => format!(" ({})", mismatches),
+                    _ => String::new(),
+                },
+                w0 = name_width,
+                w1 = part1_width,
+                w2 = part2_width,
+            ),
+            Outcome::Panicked(message) => println!(
+                "  {:w0$}  {}: {}",
+                name,
+                outcome.status(),
+                message,
+                w0 = name_width,
+            ),
+        }
+    }
+}
+
+fn print_summary(
+    names: &[String],
+    name_width: usize,
+    summary: &[(usize, Vec<(&String, Outcome)>)],
+) {
+    println!();
+    print!("summary:");
+    for name in names.iter() {
+        print!("  {:w$}", name, w = name_width);
+    }
+    println!();
+    for (day, rows) in summary.iter() {
+        print!("  day{:02} ", day);
+        for name in names.iter() {
+            let status = rows
+                .iter()
+                .find(|(row_name, _)| row_name == &name)
+                .map_or("-", |(_, outcome)| outcome.status());
+            print!("  {:w$}", status, w = name_width);
+        }
+        println!();
+    }
+    let count = |status| {
+        summary
+            .iter()
+            .flat_map(|(_, rows)| rows.iter())
+            .filter(|(_, outcome)| outcome.status() == status)
+            .count()
+    };
+    println!();
+    println!(
+        "{} ok, {} wrong, {} panicked, {} unchecked",
+        count("ok"),
+        count("WRONG"),
+        count("PANIC"),
+        count("?"),
+    );
+}
@@ -1,9 +1,10 @@
+use crate::util::Renderer;
 use reqwest::{self, header::COOKIE};
 use std::{
     error,
     fmt::{self, Display, Formatter},
     fs, io,
-    time::Instant,
+    time::{Duration, Instant},
 };
 
 #[derive(Debug)]
@@ -29,6 +30,25 @@ impl Display for Error {
 
 impl error::Error for Error {}
 
+pub type Solver = fn(&str) -> Solution;
+
+/// Fetches a day's input and renders it.
+pub type Render = fn(&mut Renderer) -> Result<(), Box<dyn error::Error>>;
+
+pub struct Solution {
+    pub part1: String,
+    pub part2: String,
+    pub parse_time: Duration,
+    pub part1_time: Duration,
+    pub part2_time: Duration,
+}
+
+impl Solution {
+    pub fn total_time(&self) -> Duration {
+        self.parse_time + self.part1_time + self.part2_time
+    }
+}
+
 pub trait Day<'a> {
     type Input;
     type ProcessedInput;
@@ -39,6 +59,12 @@ pub trait Day<'a> {
     fn solve_part1(input: Self::Input) -> (Self::ProcessedInput, String);
     fn solve_part2(input: Self::ProcessedInput) -> String;
 
+    /// Draw frames showing how the day solves `input`. Most days have
+    /// nothing to show, so draw nothing.
+    fn render(_input: &'a str, _renderer: &mut Renderer) -> io::Result<()> {
+        Ok(())
+    }
+
     fn get_input() -> Result<String, Error> {
         let input_path = format!("cache/day{}.in", Self::DAY);
         fs::read_to_string(&input_path).or_else(|_| {
@@ -63,32 +89,41 @@ pub trait Day<'a> {
         })
     }
 
+    fn solve(input: &'a str) -> Solution {
+        let start_time = Instant::now();
+        let input = Self::parse(input);
+        let parsed_time = Instant::now();
+        let (processed_input, part1) = Self::solve_part1(input);
+        let part1_time = Instant::now();
+        let part2 = Self::solve_part2(processed_input);
+        let part2_time = Instant::now();
+        Solution {
+            part1,
+            part2,
+            parse_time: parsed_time - start_time,
+            part1_time: part1_time - parsed_time,
+            part2_time: part2_time - part1_time,
+        }
+    }
+
     fn solve_and_print(input: &'a str) {
         println!();
         println!("day{:02}:", Self::DAY);
 
-        let start_time = Instant::now();
-        let input = Self::parse(input);
-        let parsed_time = Instant::now();
+        let solution = Self::solve(input);
         println!(
             "  parsing: ... (elapsed {}ms)",
-            1000.0 * (parsed_time - start_time).as_secs_f32()
+            1000.0 * solution.parse_time.as_secs_f32()
         );
-
-        let (processed_input, part1_answer) = Self::solve_part1(input);
-        let part1_time = Instant::now();
         println!(
             "  part1: {} (elapsed {}ms)",
-            part1_answer,
-            1000.0 * (part1_time - parsed_time).as_secs_f32()
+            solution.part1,
+            1000.0 * solution.part1_time.as_secs_f32()
         );
-
-        let part2_answer = Self::solve_part2(processed_input);
-        let part2_time = Instant::now();
         println!(
             "  part2: {} (elapsed {}ms)",
-            part2_answer,
-            1000.0 * (part2_time - part1_time).as_secs_f32()
+            solution.part2,
+            1000.0 * solution.part2_time.as_secs_f32()
         )
     }
 }
@@ -35,8 +35,12 @@ impl<'a> Day<'a> for Day03 {
     }
 
     fn solve_part1((width, values): Self::Input) -> (Self::ProcessedInput, String) {
-        let gamma = util::unradix((0..width).map(|n| most_common_bit(&values, n).into()), 2);
-        let epsilon = (!gamma) & ((1 << width) - 1);
+        let common = (0..width).map(|n| most_common_bit(&values, n)).collect::<Vec<_>>();
+        // epsilon takes the other bit in each of the `width` places
+        let rate = |flip: bool| {
+            util::from_digits_rev(common.iter().map(|&bit| (bit != flip).into()), 2).unwrap()
+        };
+        let (gamma, epsilon) = (rate(false), rate(true));
         ((width, values), (gamma * epsilon).to_string())
     }
 
@@ -1,8 +1,9 @@
 use crate::{
     day::Day,
-    util::{CollectArray, DrainFilterMappable},
+    util::{self, Cursor, DrainFilterMappable, ParseResult},
 };
 use itertools::iproduct;
+use std::{convert::TryInto, vec};
 
 const SIZE: usize = 5;
 const WINS: [u32; 10] = [
@@ -24,20 +25,27 @@ pub struct Board {
 }
 
 impl Board {
-    fn parse(rows: &[&str]) -> Board {
-        Board {
+    fn parse<'a>(cursor: &mut Cursor<'a>) -> ParseResult<Board> {
+        let row = |cursor: &mut Cursor<'a>| {
+            let mut row = [0; SIZE];
+            for number in &mut row {
+                cursor.take_while(|c| c == ' ');
+                *number = cursor.int()?;
+            }
+            Ok(row)
+        };
+        let start = *cursor;
+        let rows = cursor.lines(row)?;
+        Ok(Board {
             rows: rows
-                .iter()
-                .map(|row| {
-                    row.split_whitespace()
-                        .map(|n| n.parse().unwrap())
-                        .collect_array()
-                })
-                .collect_array(),
+                .try_into()
+                .map_err(|_| start.error(format_args!("{} rows", SIZE)))?,
             marks: 0,
-        }
+        })
     }
 
+    // clippy mistakes the win check for a search for `self.marks & win`
+    #[allow(clippy::manual_contains)]
     fn call(&mut self, number: u32) -> Option<u32> {
         self.marks |= iproduct!(0..SIZE, 0..SIZE)
             .filter(|&(r, c)| self.rows[r][c] == number)
@@ -53,27 +61,43 @@ impl Board {
     }
 }
 
+pub struct Scores {
+    numbers: vec::IntoIter<u32>,
+    boards: Vec<Board>,
+}
+
+impl Iterator for Scores {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        let boards = &mut self.boards;
+        self.numbers
+            .find_map(|n| boards.drain_filter_map(|b| b.call(n)).last())
+    }
+}
+
 pub struct Day04;
 
 impl<'a> Day<'a> for Day04 {
-    type Input = (impl 'a + Iterator<Item = u32>, Vec<Board>);
-    type ProcessedInput = impl Iterator<Item = u32>;
+    type Input = (Vec<u32>, Vec<Board>);
+    type ProcessedInput = Scores;
 
     const DAY: usize = 4;
 
     fn parse(input: &'a str) -> Self::Input {
-        let lines = input.lines().collect::<Vec<_>>();
-        (
-            lines[0].split(',').map(|n| n.parse().unwrap()),
-            lines[2..]
-                .split(|s| s.is_empty())
-                .map(Board::parse)
-                .collect(),
-        )
+        util::parse(input, |cursor| {
+            let numbers = cursor.separated(",", Cursor::int)?;
+            cursor.literal("\n\n")?;
+            Ok((numbers, cursor.sections(Board::parse)?))
+        })
+        .unwrap()
     }
 
-    fn solve_part1((numbers, mut boards): Self::Input) -> (Self::ProcessedInput, String) {
-        let mut scores = numbers.filter_map(move |n| boards.drain_filter_map(|b| b.call(n)).last());
+    fn solve_part1((numbers, boards): Self::Input) -> (Self::ProcessedInput, String) {
+        let mut scores = Scores {
+            numbers: numbers.into_iter(),
+            boards,
+        };
         let score = scores.next().unwrap();
         (scores, score.to_string())
     }
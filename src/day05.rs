@@ -1,5 +1,9 @@
-use crate::{day::Day, util::BitSet};
+use crate::{
+    day::Day,
+    util::{BitSet, Frame, Grid, Interval, Renderer},
+};
 use itertools::Itertools;
+use std::io;
 
 pub struct Line {
     start: (i32, i32),
@@ -28,22 +32,19 @@ impl Line {
             if self.delta.0 * (o.start.1 - self.start.1)
                 == self.delta.1 * (o.start.0 - self.start.0)
             {
-                let endpoints = |s: i32, e: i32, os: i32, oe: i32, d: i32, od: i32| {
-                    if d == 0 {
-                        (s, e)
-                    } else {
-                        let o_fst = ((os + oe) + d * od * (os - oe)) / 2;
-                        let o_lst = os + oe - o_fst;
-                        (d * (d * s).max(d * o_fst), d * (d * e).min(d * o_lst))
-                    }
+                // collinear, so they overlap where their spans along the
+                // axis they move in do
+                let axis = |p: (i32, i32)| if self.delta.0 != 0 { p.0 } else { p.1 };
+                let span = |l: &Line| {
+                    let (s, e) = (axis(l.start) as i64, axis(l.end) as i64);
+                    Interval::closed(s.min(e), s.max(e))
                 };
                 let (dx, dy) = (self.delta.0.signum(), self.delta.1.signum());
-                let (odx, ody) = (o.delta.0.signum(), o.delta.1.signum());
-                let (sx, ex) = endpoints(self.start.0, self.end.0, o.start.0, o.end.0, dx, odx);
-                let (sy, ey) = endpoints(self.start.1, self.end.1, o.start.1, o.end.1, dy, ody);
-                let (nx, ny) = (dx * (ex - sx), dy * (ey - sy));
-                if nx >= 0 && ny >= 0 {
-                    (0..=nx.max(ny)).for_each(|i| f((sx + i * dx, sy + i * dy)));
+                if let Some(overlap) = span(self).intersection(span(o)) {
+                    overlap.iter().for_each(|c| {
+                        let i = (c as i32 - axis(self.start)).abs();
+                        f((self.start.0 + i * dx, self.start.1 + i * dy))
+                    });
                 }
             }
         } else {
@@ -73,7 +74,9 @@ fn count_overlaps<'a>(lines: impl Clone + Iterator<Item = &'a Line>) -> usize {
     let mut overlaps = BitSet::new();
     let pack = |(x, y)| (x + y * 1000) as u32;
     for (line, other_line) in lines.tuple_combinations() {
-        line.for_overlaps(other_line, |point| overlaps.insert(pack(point)));
+        line.for_overlaps(other_line, |point| {
+            overlaps.insert(pack(point));
+        });
     }
     overlaps.len()
 }
@@ -99,6 +102,23 @@ impl<'a> Day<'a> for Day05 {
     fn solve_part2(lines: Self::ProcessedInput) -> String {
         count_overlaps(lines.iter()).to_string()
     }
+
+    fn render(input: &'a str, renderer: &mut Renderer) -> io::Result<()> {
+        // every vent, with the points where they overlap brightest
+        let lines = Self::parse(input);
+        let width = lines.iter().map(|l| l.start.0.max(l.end.0)).max().unwrap_or(0) + 1;
+        let height = lines.iter().map(|l| l.start.1.max(l.end.1)).max().unwrap_or(0) + 1;
+        let mut vents = Grid::from_fn(width as usize, height as usize, |_| 0u8);
+        for line in &lines {
+            let (dx, dy) = (line.delta.0.signum(), line.delta.1.signum());
+            for i in 0..=line.delta.0.abs().max(line.delta.1.abs()) {
+                let point = (line.start.0 + i * dx, line.start.1 + i * dy);
+                let vent = &mut vents[(point.0 as usize, point.1 as usize)];
+                *vent = vent.saturating_add(1);
+            }
+        }
+        renderer.draw(&Frame::gray(&vents, |&n| [0, 96, 255][n.min(2) as usize]))
+    }
 }
 
 #[cfg(test)]
@@ -127,6 +147,14 @@ mod test_day05 {
         assert_eq!(part1, "5");
         assert_eq!(part2, "12");
     }
+
+    #[test]
+    fn test_day05_render() {
+        let mut renderer = Renderer::memory();
+        Day05::render(EXAMPLE, &mut renderer).unwrap();
+        let sizes = renderer.drawn().iter().map(|f| (f.width(), f.height()));
+        assert_eq!(sizes.collect::<Vec<_>>(), [(10, 10)]);
+    }
 }
 
 bench_day!(05);
@@ -1,17 +1,80 @@
-use crate::day::Day;
+use crate::{
+    day::Day,
+    util::{self, BigUint, Matrix, Scalar},
+};
 
 pub struct Day06;
 
-fn simulate(populations: &mut [u64; 9], time: u32) {
-    for _ in 0..time {
-        populations.rotate_left(1);
-        populations[6] += populations[8];
+impl Day06 {
+    /// The number of fish after `days`, in O(log days) by raising the daily
+    /// transition to a power. `None` if the count overflows `T`.
+    pub fn population_after<T: Scalar + From<u64>>(populations: &[u64; 9], days: u64) -> Option<T> {
+        let births = [(0, 6), (0, 8)];
+        let ageing = (1..9).map(|timer| (timer, timer - 1));
+        let day = Matrix::from_transitions(9, ageing.chain(births));
+        let populations = populations.map(T::from);
+        util::iterate_linear(&day, &populations, days)?
+            .into_iter()
+            .try_fold(T::zero(), T::checked_add)
+    }
+}
+
+/// The number of fish with each timer value, in `u64`s until a count
+/// overflows and in `BigUint`s from then on.
+#[derive(Clone)]
+pub enum Populations {
+    Small([u64; 9]),
+    Big([BigUint; 9]),
+}
+
+/// Move the fish on a day, or leave them be and return `None` if a count
+/// would overflow.
+fn step<T: Scalar>(populations: &mut [T; 9]) -> Option<()> {
+    let spawned = populations[0].clone().checked_add(populations[7].clone())?;
+    populations.rotate_left(1);
+    populations[6] = spawned;
+    Some(())
+}
+
+fn total<T: Scalar>(populations: &[T; 9]) -> Option<T> {
+    populations.iter().cloned().try_fold(T::zero(), T::checked_add)
+}
+
+impl Populations {
+    fn promote(&mut self) -> &mut [BigUint; 9] {
+        if let Self::Small(populations) = self {
+            *self = Self::Big(populations.map(BigUint::from));
+        }
+        match self {
+            Self::Big(populations) => populations,
+            Self::Small(_) => unreachable!(),
+        }
+    }
+
+    fn simulate(&mut self, time: u32) {
+        for _ in 0..time {
+            if let Self::Small(populations) = self {
+                if step(populations).is_some() {
+                    continue;
+                }
+            }
+            step(self.promote()).unwrap();
+        }
+    }
+
+    fn count(&mut self) -> String {
+        if let Self::Small(populations) = self {
+            if let Some(count) = total(populations) {
+                return count.to_string();
+            }
+        }
+        total(self.promote()).unwrap().to_string()
     }
 }
 
 impl<'a> Day<'a> for Day06 {
     type Input = [u64; 9];
-    type ProcessedInput = Self::Input;
+    type ProcessedInput = Populations;
 
     const DAY: usize = 6;
 
@@ -23,20 +86,23 @@ impl<'a> Day<'a> for Day06 {
         populations
     }
 
-    fn solve_part1(mut populations: Self::Input) -> (Self::ProcessedInput, String) {
-        simulate(&mut populations, 80);
-        (populations, populations.iter().sum::<u64>().to_string())
+    fn solve_part1(populations: Self::Input) -> (Self::ProcessedInput, String) {
+        let mut populations = Populations::Small(populations);
+        populations.simulate(80);
+        let ans = populations.count();
+        (populations, ans)
     }
 
     fn solve_part2(mut populations: Self::ProcessedInput) -> String {
-        simulate(&mut populations, 256 - 80);
-        populations.iter().sum::<u64>().to_string()
+        populations.simulate(256 - 80);
+        populations.count()
     }
 }
 
 #[cfg(test)]
 mod test_day06 {
     use super::*;
+    use crate::util::Modular;
 
     const EXAMPLE: &str = "3,4,3,1,2";
 
@@ -48,28 +114,23 @@ mod test_day06 {
         assert_eq!(part1, "5934");
         assert_eq!(part2, "26984457539");
     }
+
+    #[test]
+    fn test_day06_population_after() {
+        let input = Day06::parse(EXAMPLE);
+        let population = Day06::population_after::<u64>(&input, 256);
+        assert_eq!(population, Some(26984457539));
+        assert_eq!(Day06::population_after::<u64>(&input, 1_000_000_000_000), None);
+        let population = Day06::population_after::<Modular<1_000_000_007>>(&input, 256);
+        assert_eq!(population.unwrap().value(), 26984457539 % 1_000_000_007);
+        assert!(Day06::population_after::<Modular<1_000_000_007>>(&input, 1 << 40).is_some());
+        let population = Day06::population_after::<BigUint>(&input, 1000).unwrap();
+        let mut populations = Populations::Small(input);
+        populations.simulate(1000);
+        assert_eq!(population.to_string(), populations.count());
+        assert_eq!(population.to_string(), "379589061144698259131825683795505058481");
+    }
 }
 
 bench_day!(06);
 
-/*
- * Alternate solution:
- *
- * ```python
- * import numpy as np
- *
- * steps = np.matrix([[0, 0, 0, 0, 0, 0, 1, 0, 1],
- *                    [1, 0, 0, 0, 0, 0, 0, 0, 0],
- *                    [0, 1, 0, 0, 0, 0, 0, 0, 0],
- *                    [0, 0, 1, 0, 0, 0, 0, 0, 0],
- *                    [0, 0, 0, 1, 0, 0, 0, 0, 0],
- *                    [0, 0, 0, 0, 1, 0, 0, 0, 0],
- *                    [0, 0, 0, 0, 0, 1, 0, 0, 0],
- *                    [0, 0, 0, 0, 0, 0, 1, 0, 0],
- *                    [0, 0, 0, 0, 0, 0, 0, 1, 0]])
- * coeffs80 = (steps ** 80) @ np.array([1] * 9)
- * coeffs256 = (steps ** 256) @ np.array([1] * 9)
- * ```
- *
- * Then part1 = coeffs80.dot(populations), part2 = coeffs256.dot(populations).
- */
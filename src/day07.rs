@@ -17,7 +17,7 @@ impl<'a> Day<'a> for Day07 {
     }
 
     fn solve_part1(mut heights: Self::Input) -> (Self::ProcessedInput, String) {
-        let argmin = *util::qselect(heights.len() / 2, &mut heights);
+        let argmin = *util::median(&mut heights);
         let fuel = heights.iter().map(|h| h.abs_diff(argmin)).sum::<u32>();
         (heights, fuel.to_string())
     }
@@ -51,7 +51,7 @@ impl<'a> Day<'a> for Day08 {
             .map(|display| {
                 let one = find_nsegments(&display[..10], 2);
                 let four = find_nsegments(&display[..10], 4);
-                util::unradix(display[10..].iter().map(|s| decode(s, one, four)).rev(), 10)
+                util::from_digits(display[10..].iter().map(|s| decode(s, one, four)), 10).unwrap()
             })
             .sum::<u64>()
             .to_string()
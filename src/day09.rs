@@ -1,57 +1,80 @@
-use crate::{day::Day, util};
-use itertools::{iproduct, Itertools};
-
-fn adjacents<const WIDTH: usize, const HEIGHT: usize>(
-    (r, c): (usize, usize),
-) -> impl Iterator<Item = (usize, usize)> {
-    [(1, 0), (0, 1), (usize::MAX, 0), (0, usize::MAX)]
-        .iter()
-        .map(move |&(dr, dc)| (r.wrapping_add(dr), c.wrapping_add(dc)))
-        .filter(|&(r, c)| r < HEIGHT && c < WIDTH)
+use crate::{
+    day::Day,
+    util::{self, Frame, Grid, Renderer, UnionFind},
+};
+use itertools::Itertools;
+use std::io;
+
+/// The basins, which are bounded by 9s, so are just the components of the
+/// other points, as sets of the points' indices in the grid. This takes a
+/// single pass over the grid, joining each point to the ones above it and
+/// to its left.
+fn basins(heights: &Grid<u32>) -> UnionFind {
+    let index = |(x, y)| x + y * heights.width();
+    let mut basins = UnionFind::new(heights.len());
+    for (x, y) in heights.points().filter(|&p| heights[p] != 9) {
+        for n in [x.checked_sub(1).map(|x| (x, y)), y.checked_sub(1).map(|y| (x, y))] {
+            if let Some(n) = n.filter(|&n| heights[n] != 9) {
+                basins.union(index((x, y)), index(n));
+            }
+        }
+    }
+    basins
+}
+
+fn basin_sizes_by_union_find(heights: &Grid<u32>) -> Vec<usize> {
+    let index = |(x, y)| x + y * heights.width();
+    let mut basins = basins(heights);
+    let roots = heights
+        .points()
+        .filter(|&p| heights[p] != 9)
+        .map(index)
+        .filter(|&i| basins.find(i) == i)
+        .collect::<Vec<_>>();
+    roots.into_iter().map(|root| basins.size(root)).collect()
 }
 
-pub struct Day09Generic<const WIDTH: usize, const HEIGHT: usize>;
-pub type Day09 = Day09Generic<100, 100>;
+pub struct Day09;
 
-impl<'a, const WIDTH: usize, const HEIGHT: usize> Day<'a> for Day09Generic<WIDTH, HEIGHT> {
-    type Input = Vec<u32>;
-    type ProcessedInput = (Vec<(usize, usize)>, Vec<u32>);
+impl<'a> Day<'a> for Day09 {
+    type Input = Grid<u32>;
+    type ProcessedInput = Self::Input;
 
     const DAY: usize = 9;
 
     fn parse(input: &'a str) -> Self::Input {
-        input
-            .lines()
-            .flat_map(|line| line.chars().map(|c| c.to_digit(10).unwrap()))
-            .collect()
+        util::parse(input, |cursor| cursor.grid(|c| c.to_digit(10))).unwrap()
     }
 
     fn solve_part1(heights: Self::Input) -> (Self::ProcessedInput, String) {
-        let mut low_points = Vec::new();
-        let mut risk = 0;
-        for (r, c) in iproduct!(0..HEIGHT, 0..WIDTH) {
-            let height = heights[WIDTH * r + c];
-            if adjacents::<WIDTH, HEIGHT>((r, c)).all(|(nr, nc)| heights[WIDTH * nr + nc] > height)
-            {
-                low_points.push((r, c));
-                risk += height + 1;
-            }
-        }
-        ((low_points, heights), risk.to_string())
+        let risk = heights
+            .points()
+            .filter(|&p| heights.neighbours4(p).all(|n| heights[n] > heights[p]))
+            .map(|p| heights[p] + 1)
+            .sum::<u32>();
+        (heights, risk.to_string())
     }
 
-    fn solve_part2((low_points, heights): Self::ProcessedInput) -> String {
-        let neighbours =
-            |p| adjacents::<WIDTH, HEIGHT>(p).filter(|&(r, c)| heights[WIDTH * r + c] != 9);
-        low_points
+    fn solve_part2(heights: Self::ProcessedInput) -> String {
+        basin_sizes_by_union_find(&heights)
             .into_iter()
-            .map(|point| util::bfs(point, neighbours, |_| {}).len())
             .sorted()
             .rev()
             .take(3)
             .product::<usize>()
             .to_string()
     }
+
+    fn render(input: &'a str, renderer: &mut Renderer) -> io::Result<()> {
+        // each basin in a colour of its own, between black 9s
+        let heights = Self::parse(input);
+        let mut basins = basins(&heights);
+        let points = Grid::from_fn(heights.width(), heights.height(), |point| point);
+        renderer.draw(&Frame::color(&points, |&(x, y)| match heights[(x, y)] {
+            9 => [0; 3],
+            _ => util::palette(basins.find(x + y * heights.width())),
+        }))
+    }
 }
 
 #[cfg(test)]
@@ -69,12 +92,51 @@ mod test_day09 {
 
     #[test]
     fn test_day09_examples() {
-        let input = Day09Generic::<10, 5>::parse(EXAMPLE);
-        let (input, part1) = Day09Generic::<10, 5>::solve_part1(input);
-        let part2 = Day09Generic::<10, 5>::solve_part2(input);
+        let input = Day09::parse(EXAMPLE);
+        let (input, part1) = Day09::solve_part1(input);
+        let part2 = Day09::solve_part2(input);
         assert_eq!(part1, "15");
         assert_eq!(part2, "1134");
     }
+
+    /// The sizes of the basins, found by searching each one out in turn, to
+    /// check the union-find against.
+    fn basin_sizes_by_search(heights: &Grid<u32>) -> Vec<usize> {
+        let basin_points = heights.points().filter(|&p| heights[p] != 9);
+        let neighbours = |p| heights.neighbours4(p).filter(|&n| heights[n] != 9);
+        let (labels, basins) = util::connected_components(basin_points, neighbours);
+        let mut sizes = vec![0; basins];
+        labels.values().for_each(|&basin| sizes[basin] += 1);
+        sizes
+    }
+
+    #[test]
+    fn test_day09_basin_methods_agree() {
+        let mut random = util::Random::new(0xdead_beef);
+        let example = Day09::parse(EXAMPLE);
+        let grids = (0..50).map(|_| {
+            let (width, height) = (1 + random.below(30), 1 + random.below(30));
+            Grid::from_fn(width, height, |_| match random.below(3) {
+                0 => 9,
+                _ => random.below(9) as u32,
+            })
+        });
+        for heights in std::iter::once(example).chain(grids) {
+            let mut by_search = basin_sizes_by_search(&heights);
+            let mut by_union_find = basin_sizes_by_union_find(&heights);
+            by_search.sort_unstable();
+            by_union_find.sort_unstable();
+            assert_eq!(by_search, by_union_find);
+        }
+    }
+
+    #[test]
+    fn test_day09_render() {
+        let mut renderer = Renderer::memory();
+        Day09::render(EXAMPLE, &mut renderer).unwrap();
+        let sizes = renderer.drawn().iter().map(|f| (f.width(), f.height()));
+        assert_eq!(sizes.collect::<Vec<_>>(), [(10, 5)]);
+    }
 }
 
 bench_day!(09);
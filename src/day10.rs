@@ -3,11 +3,12 @@ use crate::{
     util::{self, Summation},
 };
 use itertools::Itertools;
+use std::str::Lines;
 
 pub struct Day10;
 
 impl<'a> Day<'a> for Day10 {
-    type Input = impl 'a + Iterator<Item = &'a str>;
+    type Input = Lines<'a>;
     type ProcessedInput = Vec<String>;
 
     const DAY: usize = 10;
@@ -35,9 +36,13 @@ impl<'a> Day<'a> for Day10 {
     fn solve_part2(completions: Self::ProcessedInput) -> String {
         let mut scores = completions
             .into_iter()
-            .map(|s| util::unradix(s.chars().map(|c| 1 + ")]}>".find(c).unwrap() as u64), 5))
+            .map(|s| {
+                // the closer stacked last is typed first, so is most significant
+                let digits = s.chars().map(|c| 1 + ")]}>".find(c).unwrap() as u64);
+                util::from_digits_rev(digits, 5).unwrap()
+            })
             .collect::<Vec<_>>();
-        util::qselect(scores.len() / 2, &mut scores).to_string()
+        util::median(&mut scores).to_string()
     }
 }
 
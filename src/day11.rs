@@ -1,74 +1,105 @@
-use crate::day::Day;
+use crate::{
+    day::Day,
+    util::{self, Automaton, Boundary, Frame, Grid, Renderer, MOORE},
+};
+use std::io;
 
-fn for_adjacents<F: FnMut(usize)>(i: usize, mut f: F) {
-    macro_rules! for_each {
-        ($($xs:expr),+) => {{
-            $(f((i as i32 + $xs) as usize));+
-        }}
-    }
-    match (i / 10, i % 10) {
-        (0, 0) => for_each!(1, 10, 11),
-        (0, 9) => for_each!(-1, 10, 9),
-        (9, 0) => for_each!(1, -10, -9),
-        (9, 9) => for_each!(-1, -10, -11),
-        (0, _) => for_each!(1, -1, 10, 11, 9),
-        (9, _) => for_each!(1, -1, -10, -11, -9),
-        (_, 0) => for_each!(1, -10, -9, 10, 11),
-        (_, 9) => for_each!(-1, -10, -11, 10, 9),
-        (_, _) => for_each!(1, -1, 10, -10, 9, -9, 11, -11),
-    }
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Octopus {
+    Charging(u8),
+    Flashing,
+    Flashed,
 }
 
-fn flash(flasher: usize, energy_levels: &mut [u32]) -> usize {
-    let mut flashes = 1;
-    energy_levels[flasher] = u32::MAX;
-    for_adjacents(flasher, |i| {
-        if energy_levels[i] != u32::MAX {
-            energy_levels[i] += 1;
-            if energy_levels[i] > 8 {
-                flashes += flash(i, energy_levels);
+type Octopuses = Automaton<Octopus, fn(&Octopus, &[&Octopus]) -> Octopus>;
+
+/// One round of a step's chain reaction: each flash goes off once, charging
+/// the octopuses around it, which may then flash themselves.
+fn charge(octopus: &Octopus, neighbours: &[&Octopus]) -> Octopus {
+    match *octopus {
+        Octopus::Charging(energy) => {
+            let flashes = neighbours.iter().filter(|&&&n| n == Octopus::Flashing);
+            match energy + flashes.count() as u8 {
+                energy if energy > 9 => Octopus::Flashing,
+                energy => Octopus::Charging(energy),
             }
         }
-    });
-    flashes
+        _ => Octopus::Flashed,
+    }
 }
 
-fn step(energy_levels: &mut [u32]) -> usize {
+fn octopuses(energy_levels: Grid<u32>) -> Octopuses {
+    let octopuses = energy_levels.map(|&energy| Octopus::Charging(energy as u8));
+    Automaton::new(octopuses, &MOORE, Boundary::Fixed, charge as _)
+}
+
+fn step(octopuses: &mut Octopuses) -> usize {
+    for octopus in octopuses.grid_mut().iter_mut() {
+        if let Octopus::Charging(energy) = *octopus {
+            *octopus = match energy {
+                9 => Octopus::Flashing,
+                _ => Octopus::Charging(energy + 1),
+            };
+        }
+    }
+    octopuses.run_until_stable();
     let mut flashes = 0;
-    for i in 0..100 {
-        if energy_levels[i] == 9 {
-            flashes += flash(i, energy_levels);
+    for octopus in octopuses.grid_mut().iter_mut() {
+        if *octopus == Octopus::Flashed {
+            *octopus = Octopus::Charging(0);
+            flashes += 1;
         }
     }
-    energy_levels
-        .iter_mut()
-        .for_each(|o| *o = o.wrapping_add(1));
     flashes
 }
 
 pub struct Day11;
 
 impl<'a> Day<'a> for Day11 {
-    type Input = Vec<u32>;
-    type ProcessedInput = Self::Input;
+    type Input = Grid<u32>;
+    type ProcessedInput = Octopuses;
 
     const DAY: usize = 11;
 
     fn parse(input: &'a str) -> Self::Input {
-        input.chars().filter_map(|c| c.to_digit(10)).collect()
+        util::parse(input, |cursor| cursor.grid(|c| c.to_digit(10))).unwrap()
     }
 
-    fn solve_part1(mut energy_levels: Self::Input) -> (Self::ProcessedInput, String) {
-        let flashes = (0..100).map(|_| step(&mut energy_levels)).sum::<usize>();
-        (energy_levels, flashes.to_string())
+    fn solve_part1(energy_levels: Self::Input) -> (Self::ProcessedInput, String) {
+        let mut octopuses = octopuses(energy_levels);
+        let flashes = (0..100).map(|_| step(&mut octopuses)).sum::<usize>();
+        (octopuses, flashes.to_string())
     }
 
-    fn solve_part2(mut energy_levels: Self::ProcessedInput) -> String {
+    fn solve_part2(mut octopuses: Self::ProcessedInput) -> String {
+        let size = octopuses.grid().len();
         (101..)
-            .find(|_| step(&mut energy_levels) == 100)
+            .find(|_| step(&mut octopuses) == size)
             .unwrap()
             .to_string()
     }
+
+    fn render(input: &'a str, renderer: &mut Renderer) -> io::Result<()> {
+        // every step up to the first where they all flash, and the ones that
+        // just flashed are back at 0, so show those brightest; some octopuses
+        // never all flash at once, so give up on them eventually
+        const MAX_STEPS: usize = 1000;
+        let shade = |octopus: &Octopus| match *octopus {
+            Octopus::Charging(0) => 255,
+            Octopus::Charging(energy) => 20 * energy,
+            _ => unreachable!("steps end with every octopus charging"),
+        };
+        let mut octopuses = octopuses(Self::parse(input));
+        renderer.draw(&Frame::gray(octopuses.grid(), shade))?;
+        for _ in 0..MAX_STEPS {
+            let flashes = step(&mut octopuses);
+            renderer.draw(&Frame::gray(octopuses.grid(), shade))?;
+            if flashes == octopuses.grid().len() {
+                break;
+            }
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -97,6 +128,21 @@ mod test_day11 {
         assert_eq!(part1, "1656");
         assert_eq!(part2, "195");
     }
+
+    #[test]
+    fn test_day11_render() {
+        // the start, then every step up to when they all flash
+        let mut renderer = Renderer::memory();
+        Day11::render(EXAMPLE, &mut renderer).unwrap();
+        assert_eq!(renderer.frames(), 1 + 195);
+        let frames = renderer.drawn();
+        assert!(frames.iter().all(|f| (f.width(), f.height()) == (10, 10)));
+
+        // these two take turns flashing forever
+        let mut renderer = Renderer::memory();
+        Day11::render("02\n", &mut renderer).unwrap();
+        assert_eq!(renderer.frames(), 1 + 1000);
+    }
 }
 
 bench_day!(11);
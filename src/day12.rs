@@ -1,97 +1,100 @@
-use crate::day::Day;
+use crate::{
+    day::Day,
+    util::{self, BigUint, Graph, Memo, Scalar},
+};
 use hashbrown::HashMap;
-use itertools::Itertools;
 
-type Cave = [u8; 2];
-
-const START: Cave = [0, 0];
-const END: Cave = [!0, !0];
-
-fn calculate_weights(edges: HashMap<Cave, Vec<Cave>>) -> HashMap<Cave, HashMap<Cave, u32>> {
-    let mut weighted_edges = HashMap::new();
-    let mut add_edge = |start, end| {
-        *weighted_edges
-            .entry(start)
-            .or_insert_with(HashMap::new)
-            .entry(end)
-            .or_insert(0) += 1
+/// The caves with the big ones contracted away, so that the edges go from
+/// small cave to small cave, weighted by the number of ways to get there.
+type Caves<'a> = Graph<&'a str>;
+
+/// A small cave on the way, the small caves visited so far (as bits) and
+/// whether one of them may still be visited twice.
+type Progress = (usize, u64, bool);
+
+fn completions<T: Scalar + From<u64>>(caves: &Caves, allow_dups: bool) -> Option<T> {
+    let (start, end) = (caves.node(&"start").unwrap(), caves.node(&"end").unwrap());
+    let small = caves.nodes().filter(|&cave| cave != end).collect::<Vec<_>>();
+    assert!(small.len() <= 64, "too many small caves");
+    let bits = small
+        .into_iter()
+        .zip(0..)
+        .map(|(cave, i)| (cave, 1 << i))
+        .collect::<HashMap<_, u64>>();
+    let dependencies = |&(cave, visited, allow_dups): &Progress| {
+        // the paths stop at the end, so don't go on from there
+        let next = caves.edges(cave).filter(|&(next, _)| cave != end && next != start);
+        next.filter_map(|(next, _)| match bits.get(&next) {
+            None => Some((end, 0, false)),
+            Some(&bit) if visited & bit == 0 => Some((next, visited | bit, allow_dups)),
+            Some(_) if allow_dups => Some((next, visited, false)),
+            Some(_) => None,
+        })
+        .collect::<Vec<_>>()
     };
-    for &small_cave in edges
-        .keys()
-        .filter(|&&cave| cave == START || cave[0].is_ascii_lowercase())
-    {
-        for &end in edges[&small_cave].iter() {
-            if end[0].is_ascii_uppercase() {
-                for &next_small_cave in edges[&end].iter() {
-                    add_edge(small_cave, next_small_cave);
-                }
-            } else {
-                add_edge(small_cave, end);
-            }
+    let combine = |&(cave, _, _): &Progress, completions: Vec<(Progress, Option<T>)>| {
+        if cave == end {
+            return Some(T::one());
         }
-    }
-    weighted_edges
+        completions
+            .into_iter()
+            .try_fold(T::zero(), |total, ((next, _, _), count)| {
+                let weight = T::from(caves.weight(cave, next).unwrap() as u64);
+                total.checked_add(weight.checked_mul(count?)?)
+            })
+    };
+    Memo::new().get_iterative((start, 0, allow_dups), dependencies, combine)
 }
 
-fn completions(
-    path: Vec<Cave>,
-    small_edges: &HashMap<Cave, HashMap<Cave, u32>>,
-    allow_dups: bool,
-) -> u32 {
-    small_edges[path.last().unwrap_or(&START)]
-        .iter()
-        .map(|(&cave, &weight)| {
-            if cave == END {
-                weight
-            } else if !path.contains(&cave) {
-                weight * completions([path.clone(), vec![cave]].concat(), small_edges, allow_dups)
-            } else if allow_dups {
-                weight * completions([path.clone(), vec![cave]].concat(), small_edges, false)
-            } else {
-                0
-            }
-        })
-        .sum()
+/// The number of paths, falling back to a `BigUint` if it doesn't fit in a
+/// `u64`.
+fn count_paths(caves: &Caves, allow_dups: bool) -> String {
+    match completions::<u64>(caves, allow_dups) {
+        Some(count) => count.to_string(),
+        None => completions::<BigUint>(caves, allow_dups)
+            .unwrap()
+            .to_string(),
+    }
 }
 
 pub struct Day12;
 
+impl Day12 {
+    /// The caves in Graphviz's DOT language, either as given or with the big
+    /// caves contracted away as the solver sees them.
+    pub fn to_dot(input: &str, contracted: bool) -> String {
+        if contracted {
+            Self::parse(input).to_dot()
+        } else {
+            util::parse(input, Caves::parse_edges).unwrap().to_dot()
+        }
+    }
+}
+
 impl<'a> Day<'a> for Day12 {
-    type Input = HashMap<Cave, HashMap<Cave, u32>>;
+    type Input = Caves<'a>;
     type ProcessedInput = Self::Input;
 
     const DAY: usize = 12;
 
     fn parse(input: &'a str) -> Self::Input {
-        let mut edges = HashMap::new();
-        let mut add_edge = |s, e| {
-            if e != START {
-                edges.entry(s).or_insert_with(Vec::new).push(e)
-            }
-        };
-        for line in input.lines() {
-            let (a, b) = line
-                .split('-')
-                .map(|cave| match cave {
-                    "start" => START,
-                    "end" => END,
-                    _ => [cave.as_bytes()[0], cave.as_bytes()[1]],
-                })
-                .next_tuple()
-                .unwrap();
-            add_edge(a, b);
-            add_edge(b, a);
-        }
-        calculate_weights(edges)
+        let mut caves = util::parse(input, Graph::parse_edges).unwrap();
+        let big = caves
+            .nodes()
+            .filter(|&cave| caves.label(cave).starts_with(|c: char| c.is_ascii_uppercase()))
+            .collect::<Vec<_>>();
+        big.into_iter().for_each(|cave| caves.contract(cave));
+        caves.merge_parallel_edges();
+        caves
     }
 
-    fn solve_part1(small_edges: Self::Input) -> (Self::ProcessedInput, String) {
-        let count = completions(Vec::new(), &small_edges, false);
-        (small_edges, count.to_string())
+    fn solve_part1(caves: Self::Input) -> (Self::ProcessedInput, String) {
+        let count = count_paths(&caves, false);
+        (caves, count)
     }
 
-    fn solve_part2(small_edges: Self::ProcessedInput) -> String {
-        completions(Vec::new(), &small_edges, true).to_string()
+    fn solve_part2(caves: Self::ProcessedInput) -> String {
+        count_paths(&caves, true)
     }
 }
 
@@ -153,6 +156,36 @@ mod test_day12 {
         assert_eq!(part2, output2);
     }
 
+    #[test]
+    fn test_day12_reduced_graph() {
+        // AA is gone, leaving start-bb twice over and a loop at each cave
+        // that was next to it
+        let dot = indoc! {r#"
+            digraph {
+                0 [label="start"];
+                2 [label="bb"];
+                3 [label="cc"];
+                4 [label="dd"];
+                5 [label="end"];
+                0 -> 2 [dir=both, label=2];
+                0 -> 3 [dir=both, label=1];
+                2 -> 3 [dir=both, label=1];
+                2 -> 4 [dir=both, label=1];
+                0 -> 5 [dir=both, label=1];
+                2 -> 5 [dir=both, label=2];
+                3 -> 5 [dir=both, label=1];
+                0 -> 0 [label=1];
+                2 -> 2 [label=1];
+                3 -> 3 [label=1];
+                5 -> 5 [label=1];
+            }
+        "#};
+        assert_eq!(Day12::to_dot(SMALL_EXAMPLE, true), dot);
+        let given = Day12::to_dot(SMALL_EXAMPLE, false);
+        assert!(given.contains("1 [label=\"AA\"];\n"));
+        assert_eq!(given.matches("dir=both").count(), 7);
+    }
+
     #[test]
     fn test_day12_examples() {
         assert_example(SMALL_EXAMPLE, "10", "36");
@@ -1,22 +1,36 @@
-use crate::{day::Day, util::CollectArray};
-use hashbrown::HashSet;
-use itertools::Itertools;
-use std::{collections::VecDeque, mem};
-
-fn fold(grid: &mut HashSet<[u16; 2]>, fold: (u8, u16)) {
-    *grid = mem::take(grid)
-        .into_iter()
-        .map(|mut point| {
+use crate::{
+    day::Day,
+    util::{self, BitSet, Cursor, Frame, Grid, Renderer},
+};
+use std::{collections::VecDeque, io};
+
+// wider than any paper, so points pack densely into a BitSet
+const STRIDE: u32 = 1 << 11;
+
+fn pack([x, y]: [u32; 2]) -> u32 {
+    assert!(x < STRIDE, "x = {} is off the paper", x);
+    x + y * STRIDE
+}
+
+fn unpack(point: u32) -> [u32; 2] {
+    [point % STRIDE, point / STRIDE]
+}
+
+fn fold(grid: &mut BitSet, fold: (u8, u32)) {
+    *grid = grid
+        .iter()
+        .map(|point| {
+            let mut point = unpack(point);
             point[fold.0 as usize] =
-                u16::min(point[fold.0 as usize], 2 * fold.1 - point[fold.0 as usize]);
-            point
+                u32::min(point[fold.0 as usize], 2 * fold.1 - point[fold.0 as usize]);
+            pack(point)
         })
         .collect();
 }
 
-fn decode(char_idx: u16, grid: &HashSet<[u16; 2]>) -> char {
+fn decode(char_idx: u32, grid: &BitSet) -> char {
     let b = char_idx * 5;
-    let check = |x, y| grid.contains(&[x, y]) as u8;
+    let check = |x, y| grid.contains(pack([x, y])) as u8;
     match (check(b, 0), check(b + 3, 0), check(b, 5), check(b + 3, 5)) {
         (0, 0, 0, 0) => 'C',
         (0, 0, 0, 1) => 'G',
@@ -36,25 +50,45 @@ fn decode(char_idx: u16, grid: &HashSet<[u16; 2]>) -> char {
     }
 }
 
+/// The dots on the paper, in white.
+fn frame(grid: &BitSet) -> Frame {
+    let (width, height) = grid.iter().map(unpack).fold((0, 0), |(width, height), [x, y]| {
+        (width.max(x as usize + 1), height.max(y as usize + 1))
+    });
+    let points = Grid::from_fn(width, height, |(x, y)| pack([x as u32, y as u32]));
+    Frame::gray(&points, |&point| if grid.contains(point) { 255 } else { 0 })
+}
+
 pub struct Day13;
 
 impl<'a> Day<'a> for Day13 {
-    type Input = (HashSet<[u16; 2]>, VecDeque<(u8, u16)>);
+    type Input = (BitSet, VecDeque<(u8, u32)>);
     type ProcessedInput = Self::Input;
 
     const DAY: usize = 13;
 
     fn parse(input: &'a str) -> Self::Input {
-        let (paper, folds) = input.split("\n\n").next_tuple().unwrap();
-        let parse_point = |s: &str| s.split(',').map(|n| n.parse().unwrap()).collect_array();
-        let parse_fold = |s: &str| {
-            let (axis, n) = s[11..].split('=').next_tuple().unwrap();
-            (axis.as_bytes()[0] - b'x', n.parse().unwrap())
+        let parse_point = |cursor: &mut Cursor| {
+            let start = *cursor;
+            let x = cursor.int()?;
+            if x >= STRIDE {
+                return Err(start.error(format_args!("an x below {}", STRIDE)));
+            }
+            cursor.literal(",")?;
+            Ok(pack([x, cursor.int()?]))
+        };
+        let parse_fold = |cursor: &mut Cursor| {
+            cursor.literal("fold along ")?;
+            let axis = if cursor.eat("x") { 0 } else { cursor.literal("y").map(|_| 1)? };
+            cursor.literal("=")?;
+            Ok((axis, cursor.int()?))
         };
-        (
-            paper.lines().map(parse_point).collect(),
-            folds.lines().map(parse_fold).collect(),
-        )
+        util::parse(input, |cursor| {
+            let paper = cursor.lines(parse_point)?;
+            cursor.literal("\n")?;
+            Ok((paper.into_iter().collect(), cursor.lines(parse_fold)?.into()))
+        })
+        .unwrap()
     }
 
     fn solve_part1((mut grid, mut folds): Self::Input) -> (Self::ProcessedInput, String) {
@@ -67,6 +101,17 @@ impl<'a> Day<'a> for Day13 {
         folds.into_iter().for_each(|f| fold(&mut grid, f));
         (0..=7).map(|i| decode(i, &grid)).collect()
     }
+
+    fn render(input: &'a str, renderer: &mut Renderer) -> io::Result<()> {
+        // the paper before and after each fold
+        let (mut grid, folds) = Self::parse(input);
+        renderer.draw(&frame(&grid))?;
+        for f in folds {
+            fold(&mut grid, f);
+            renderer.draw(&frame(&grid))?;
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -104,6 +149,20 @@ mod test_day13 {
         let (_, part1) = Day13::solve_part1(input);
         assert_eq!(part1, "17");
     }
+
+    #[test]
+    #[should_panic(expected = "an x below 2048")]
+    fn test_day13_wide_paper() {
+        Day13::parse("0,0\n2048,0\n\nfold along x=1024\n");
+    }
+
+    #[test]
+    fn test_day13_render() {
+        let mut renderer = Renderer::memory();
+        Day13::render(EXAMPLE, &mut renderer).unwrap();
+        let sizes = renderer.drawn().iter().map(|f| (f.width(), f.height()));
+        assert_eq!(sizes.collect::<Vec<_>>(), [(11, 15), (11, 5), (5, 5)]);
+    }
 }
 
 bench_day!(13);
@@ -1,68 +1,196 @@
-use crate::day::Day;
+use crate::{
+    day::Day,
+    util::{self, BigUint, Cursor, Matrix, Scalar},
+};
 use hashbrown::HashMap;
 use itertools::Itertools;
-use std::{iter, mem};
+use std::{iter, mem, ops::Sub};
 
 type Pair = (u8, u8);
 
-fn reinforce(polymer: &mut HashMap<Pair, u64>, rules: &HashMap<Pair, u8>) {
-    for (pair, count) in mem::take(polymer).into_iter() {
-        *polymer.entry((pair.0, rules[&pair])).or_insert(0) += count;
-        *polymer.entry((rules[&pair], pair.1)).or_insert(0) += count;
+fn reinforce<T: Scalar>(
+    polymer: &HashMap<Pair, T>,
+    rules: &HashMap<Pair, u8>,
+) -> Option<HashMap<Pair, T>> {
+    let mut reinforced = HashMap::with_capacity(polymer.len());
+    for (pair, count) in polymer {
+        for child in [(pair.0, rules[pair]), (rules[pair], pair.1)] {
+            let total = reinforced.entry(child).or_insert_with(T::zero);
+            *total = mem::replace(total, T::zero()).checked_add(count.clone())?;
+        }
     }
+    Some(reinforced)
 }
 
-fn diversity(last: u8, polymer: &HashMap<Pair, u64>) -> u64 {
+fn element_counts<T: Scalar>(
+    last: u8,
+    polymer: impl IntoIterator<Item = (Pair, T)>,
+) -> Option<HashMap<u8, T>> {
     let mut counts = HashMap::new();
-    for (pair, count) in polymer.iter().chain(iter::once((&(last, 0), &1))) {
-        *counts.entry(pair.0).or_insert(0) += count;
+    for (pair, count) in polymer.into_iter().chain(iter::once(((last, 0), T::one()))) {
+        let total = counts.entry(pair.0).or_insert_with(T::zero);
+        *total = mem::replace(total, T::zero()).checked_add(count)?;
     }
+    Some(counts)
+}
+
+fn diversity<T: Clone + Ord + Sub<Output = T>>(counts: &HashMap<u8, T>) -> T {
     let (min, max) = counts.values().minmax().into_option().unwrap();
-    max - min
+    max.clone() - min.clone()
+}
+
+/// The count of each pair of elements, in `u64`s until a count overflows
+/// and in `BigUint`s from then on.
+pub enum Polymer {
+    Small(HashMap<Pair, u64>),
+    Big(HashMap<Pair, BigUint>),
+}
+
+impl Polymer {
+    fn promote(&mut self) -> &mut HashMap<Pair, BigUint> {
+        if let Self::Small(polymer) = self {
+            let promoted = polymer
+                .iter()
+                .map(|(&pair, &count)| (pair, BigUint::from(count)))
+                .collect();
+            *self = Self::Big(promoted);
+        }
+        match self {
+            Self::Big(polymer) => polymer,
+            Self::Small(_) => unreachable!(),
+        }
+    }
+
+    fn grow(&mut self, rules: &HashMap<Pair, u8>, steps: u32) {
+        for _ in 0..steps {
+            if let Self::Small(polymer) = self {
+                if let Some(reinforced) = reinforce(polymer, rules) {
+                    *polymer = reinforced;
+                    continue;
+                }
+            }
+            let polymer = self.promote();
+            *polymer = reinforce(polymer, rules).unwrap();
+        }
+    }
+
+    fn diversity(&mut self, last: u8) -> String {
+        if let Self::Small(polymer) = self {
+            if let Some(counts) = element_counts(last, polymer.iter().map(|(&p, &c)| (p, c))) {
+                return diversity(&counts).to_string();
+            }
+        }
+        let polymer = self.promote().iter().map(|(&p, c)| (p, c.clone()));
+        diversity(&element_counts(last, polymer).unwrap()).to_string()
+    }
 }
 
 pub struct Day14;
 
+impl Day14 {
+    /// How many of each element there are after `steps`, in O(log steps) by
+    /// raising the insertion step (a linear map on pair counts) to a power.
+    /// `None` if a count overflows `T`.
+    pub fn element_counts_after<T: Scalar + From<u64>>(
+        last: u8,
+        polymer: &HashMap<Pair, u64>,
+        rules: &HashMap<Pair, u8>,
+        steps: u64,
+    ) -> Option<HashMap<u8, T>> {
+        let children = |pair: Pair| match rules.get(&pair) {
+            Some(&elmt) => vec![(pair.0, elmt), (elmt, pair.1)],
+            None => vec![pair],
+        };
+        let pairs = polymer
+            .keys()
+            .chain(rules.keys())
+            .flat_map(|&pair| children(pair).into_iter().chain(iter::once(pair)))
+            .unique()
+            .collect::<Vec<_>>();
+        let index = |pair| pairs.iter().position(|&p| p == pair).unwrap();
+        let transitions = pairs
+            .iter()
+            .enumerate()
+            .flat_map(|(i, &pair)| children(pair).into_iter().map(move |child| (i, child)))
+            .map(|(i, child)| (i, index(child)))
+            .collect::<Vec<_>>();
+        let step = Matrix::from_transitions(pairs.len(), transitions);
+        let counts = pairs
+            .iter()
+            .map(|pair| T::from(polymer.get(pair).copied().unwrap_or(0)))
+            .collect::<Vec<_>>();
+        let counts = util::iterate_linear(&step, &counts, steps)?;
+        element_counts(last, pairs.into_iter().zip(counts))
+    }
+
+    /// The answer to part 2 for any number of steps, if it fits in a `T`.
+    pub fn diversity_after<T: Scalar + From<u64> + Ord + Sub<Output = T>>(
+        last: u8,
+        polymer: &HashMap<Pair, u64>,
+        rules: &HashMap<Pair, u8>,
+        steps: u64,
+    ) -> Option<T> {
+        let counts = Self::element_counts_after(last, polymer, rules, steps)?;
+        Some(diversity(&counts))
+    }
+}
+
 impl<'a> Day<'a> for Day14 {
     type Input = (u8, HashMap<Pair, u64>, HashMap<Pair, u8>);
-    type ProcessedInput = Self::Input;
+    type ProcessedInput = (u8, Polymer, HashMap<Pair, u8>);
 
     const DAY: usize = 14;
 
     fn parse(input: &'a str) -> Self::Input {
-        let (template, rules) = input.split("\n\n").next_tuple().unwrap();
-        (
-            template.bytes().last().unwrap(),
-            template
-                .bytes()
-                .tuple_windows()
-                .counts()
-                .into_iter()
-                .map(|kv| (kv.0, kv.1 as u64))
-                .collect(),
-            rules
-                .lines()
-                .map(|line| line.split(" -> ").next_tuple().unwrap())
-                .map(|(pair, elmt)| ((pair.as_bytes()[0], pair.as_bytes()[1]), elmt.as_bytes()[0]))
-                .collect(),
-        )
+        let elements = |cursor: &mut Cursor<'a>, count: usize| {
+            let start = *cursor;
+            match cursor.word()? {
+                word if word.len() == count => Ok(word.as_bytes()),
+                _ => Err(start.error(format_args!("{} elements", count))),
+            }
+        };
+        let parse_rule = |cursor: &mut Cursor<'a>| {
+            let pair = elements(cursor, 2)?;
+            cursor.literal(" -> ")?;
+            Ok(((pair[0], pair[1]), elements(cursor, 1)?[0]))
+        };
+        util::parse(input, |cursor| {
+            let template = cursor.word()?.as_bytes();
+            cursor.literal("\n\n")?;
+            let rules = cursor.lines(parse_rule)?;
+            Ok((
+                *template.last().unwrap(),
+                template
+                    .iter()
+                    .copied()
+                    .tuple_windows()
+                    .counts()
+                    .into_iter()
+                    .map(|kv| (kv.0, kv.1 as u64))
+                    .collect(),
+                rules.into_iter().collect(),
+            ))
+        })
+        .unwrap()
     }
 
-    fn solve_part1((last, mut polymer, rules): Self::Input) -> (Self::ProcessedInput, String) {
-        (0..10).for_each(|_| reinforce(&mut polymer, &rules));
-        let ans = diversity(last, &polymer);
-        ((last, polymer, rules), ans.to_string())
+    fn solve_part1((last, polymer, rules): Self::Input) -> (Self::ProcessedInput, String) {
+        let mut polymer = Polymer::Small(polymer);
+        polymer.grow(&rules, 10);
+        let ans = polymer.diversity(last);
+        ((last, polymer, rules), ans)
     }
 
     fn solve_part2((last, mut polymer, rules): Self::ProcessedInput) -> String {
-        (0..30).for_each(|_| reinforce(&mut polymer, &rules));
-        diversity(last, &polymer).to_string()
+        polymer.grow(&rules, 40 - 10);
+        polymer.diversity(last)
     }
 }
 
 #[cfg(test)]
 mod test_day14 {
     use super::*;
+    use crate::util::Modular;
     use indoc::indoc;
 
     const EXAMPLE: &str = indoc! {"
@@ -94,6 +222,26 @@ mod test_day14 {
         assert_eq!(part1, "1588");
         assert_eq!(part2, "2188189693529");
     }
+
+    #[test]
+    fn test_day14_diversity_after() {
+        let (last, polymer, rules) = Day14::parse(EXAMPLE);
+        let diversity = |steps| Day14::diversity_after::<u64>(last, &polymer, &rules, steps);
+        assert_eq!(diversity(10), Some(1588));
+        assert_eq!(diversity(40), Some(2188189693529));
+        assert_eq!(diversity(1_000_000_000_000), None);
+        let counts = Day14::element_counts_after::<Modular<1_000_000_007>>(
+            last,
+            &polymer,
+            &rules,
+            1_000_000_000_000,
+        );
+        assert_eq!(counts.map(|counts| counts.len()), Some(4));
+        let diversity = Day14::diversity_after::<BigUint>(last, &polymer, &rules, 100).unwrap();
+        let mut polymer = Polymer::Small(polymer);
+        polymer.grow(&rules, 100);
+        assert_eq!(diversity.to_string(), polymer.diversity(last));
+    }
 }
 
 bench_day!(14);
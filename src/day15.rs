@@ -1,48 +1,62 @@
-use crate::{day::Day, util};
-use itertools::iproduct;
+use crate::{
+    day::Day,
+    util::{self, Frame, Grid, Point, Renderer, ShortestPath},
+};
+use hashbrown::HashSet;
+use std::io;
 
 pub struct Day15;
 
-fn min_risk(width: usize, grid: &[u8]) -> usize {
-    let risk = |(x, y)| grid[x + y * width] as usize;
-    util::dijkstra(
+fn safest_path(grid: &Grid<u8>) -> ShortestPath<Point, usize> {
+    let goal = (grid.width() - 1, grid.height() - 1);
+    // every step costs at least 1, so the manhattan distance is a lower bound
+    util::astar(
         (0, 0),
-        |p| util::grid_neighbours(p, width, width).map(|n| (risk(n), n)),
-        |p| p == (width - 1, width - 1),
+        |p| grid.neighbours4(p).map(|n| (grid[n] as usize, n)),
+        |p| p == goal,
+        |(x, y)| (goal.0 - x) + (goal.1 - y),
     )
     .unwrap()
 }
 
+fn tile(grid: &Grid<u8>) -> Grid<u8> {
+    grid.tile(5, 5, |&risk, (c, r)| 1 + (risk + (r + c) as u8 - 1) % 9)
+}
+
+/// The risk levels in shades of grey, darkest where it's safest, with the
+/// safest path in red.
+fn frame(grid: &Grid<u8>) -> Frame {
+    let path = safest_path(grid).nodes.into_iter().collect::<HashSet<_>>();
+    let points = Grid::from_fn(grid.width(), grid.height(), |point| point);
+    Frame::color(&points, |point| match path.contains(point) {
+        true => [255, 0, 0],
+        false => [25 * grid[*point]; 3],
+    })
+}
+
 impl<'a> Day<'a> for Day15 {
-    type Input = (usize, Vec<u8>);
+    type Input = Grid<u8>;
     type ProcessedInput = Self::Input;
 
     const DAY: usize = 15;
 
     fn parse(input: &'a str) -> Self::Input {
-        let width = input.lines().next().unwrap().len();
-        let grid = input
-            .lines()
-            .flat_map(|line| line.chars())
-            .map(|c| c.to_digit(10).unwrap() as u8)
-            .collect();
-        (width, grid)
+        util::parse(input, |cursor| cursor.grid(|c| c.to_digit(10).map(|n| n as u8))).unwrap()
     }
 
-    fn solve_part1((width, grid): Self::Input) -> (Self::ProcessedInput, String) {
-        let ans = min_risk(width, &grid);
-        ((width, grid), ans.to_string())
+    fn solve_part1(grid: Self::Input) -> (Self::ProcessedInput, String) {
+        let ans = safest_path(&grid).cost;
+        (grid, ans.to_string())
     }
 
-    fn solve_part2((width, grid): Self::ProcessedInput) -> String {
-        min_risk(
-            5 * width,
-            &iproduct!(0..5, 0..width, 0..5, 0..width)
-                .map(|(r, y, c, x)| grid[x + y * width] + r + c)
-                .map(|risk| 1 + (risk - 1) % 9)
-                .collect::<Vec<_>>(),
-        )
-        .to_string()
+    fn solve_part2(grid: Self::ProcessedInput) -> String {
+        safest_path(&tile(&grid)).cost.to_string()
+    }
+
+    fn render(input: &'a str, renderer: &mut Renderer) -> io::Result<()> {
+        let grid = Self::parse(input);
+        renderer.draw(&frame(&grid))?;
+        renderer.draw(&frame(&tile(&grid)))
     }
 }
 
@@ -72,6 +86,14 @@ mod test_day15 {
         assert_eq!(part1, "40");
         assert_eq!(part2, "315");
     }
+
+    #[test]
+    fn test_day15_render() {
+        let mut renderer = Renderer::memory();
+        Day15::render(EXAMPLE, &mut renderer).unwrap();
+        let sizes = renderer.drawn().iter().map(|f| (f.width(), f.height()));
+        assert_eq!(sizes.collect::<Vec<_>>(), [(10, 10), (50, 50)]);
+    }
 }
 
 bench_day!(15);
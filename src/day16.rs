@@ -1,4 +1,7 @@
-use crate::day::Day;
+use crate::{
+    day::Day,
+    util::{self, Bits, ParseResult},
+};
 use itertools::Itertools;
 
 pub enum Packet {
@@ -6,40 +9,43 @@ pub enum Packet {
     Operator(u8, u8, Vec<Packet>),
 }
 
+/// How deeply operator packets may nest, far deeper than any puzzle input
+/// goes but shallow enough that parsing can't overflow the stack.
+const MAX_DEPTH: usize = 256;
+
 impl Packet {
-    fn parse<I: Iterator<Item = u8>>(bits: &mut I) -> (usize, Self) {
-        let take_number = |n, bits: &mut I| {
-            (0..n)
-                .rev()
-                .map(|i| (bits.next().unwrap() as u64) << i)
-                .sum::<u64>()
-        };
-        let version = take_number(3, bits) as u8;
-        let type_id = take_number(3, bits) as u8;
+    fn parse(bits: &mut Bits, depth: usize) -> ParseResult<Self> {
+        if depth > MAX_DEPTH {
+            return Err(bits.error(format!("packets nested at most {} deep", MAX_DEPTH)));
+        }
+        let version = bits.take(3)? as u8;
+        let type_id = bits.take(3)? as u8;
         match type_id {
             4 => {
-                let (mut size, mut literal) = (6, 0);
-                while let Some(flag) = bits.next() {
-                    literal = 16 * literal + take_number(4, bits);
-                    size += 5;
-                    if flag == 0 {
-                        break;
+                let mut literal = 0;
+                loop {
+                    let more = bits.take(1)? == 1;
+                    literal = 16 * literal + bits.take(4)?;
+                    if !more {
+                        break Ok(Packet::Literal(version, literal));
                     }
                 }
-                (size, Packet::Literal(version, literal))
             }
             _ => {
-                let (mut size, packet_max, bit_max) = match bits.next().unwrap() {
-                    0 => (22, usize::MAX, 22 + take_number(15, bits) as usize),
-                    _ => (18, take_number(11, bits) as usize, usize::MAX),
-                };
                 let mut packets = Vec::new();
-                while size < bit_max && packets.len() < packet_max {
-                    let (subpacket_size, subpacket) = Packet::parse(bits);
-                    size += subpacket_size;
-                    packets.push(subpacket);
+                if bits.take(1)? == 0 {
+                    let length = bits.take(15)? as usize;
+                    let end = bits.position() + length;
+                    while bits.position() < end {
+                        packets.push(Packet::parse(bits, depth + 1)?);
+                    }
+                } else {
+                    let count = bits.take(11)?;
+                    for _ in 0..count {
+                        packets.push(Packet::parse(bits, depth + 1)?);
+                    }
                 }
-                (size, Packet::Operator(version, type_id, packets))
+                Ok(Packet::Operator(version, type_id, packets))
             }
         }
     }
@@ -76,13 +82,11 @@ impl<'a> Day<'a> for Day16 {
     const DAY: usize = 16;
 
     fn parse(input: &'a str) -> Self::Input {
-        Packet::parse(
-            &mut input
-                .chars()
-                .filter_map(|c| c.to_digit(16))
-                .flat_map(|n| (0..4).rev().map(move |i| ((n >> i) & 1) as u8)),
-        )
-        .1
+        util::parse(input, |cursor| {
+            let mut bits = cursor.hex_bits()?;
+            Packet::parse(&mut bits, 0)
+        })
+        .unwrap()
     }
 
     fn solve_part1(packet: Self::Input) -> (Self::ProcessedInput, String) {
@@ -127,6 +131,25 @@ mod test_day16 {
         assert_part2("9C005AC2F8F0", "0");
         assert_part2("9C0141080250320F1802104A08", "1");
     }
+
+    #[test]
+    fn test_day16_nesting_limit() {
+        // sums of one subpacket each, around a literal 7
+        let nested = |depth| {
+            let bits = "0000001".to_string() + "00000000001";
+            let bits = bits.repeat(depth) + "00010000111";
+            let bits = bits.clone() + &"0".repeat((4 - bits.len() % 4) % 4);
+            let digits = bits.as_bytes().chunks(4).map(|nibble| {
+                let nibble = std::str::from_utf8(nibble).unwrap();
+                format!("{:X}", u8::from_str_radix(nibble, 2).unwrap())
+            });
+            let hex = digits.collect::<String>();
+            util::parse(&hex, |cursor| Packet::parse(&mut cursor.hex_bits()?, 0))
+        };
+        assert_eq!(nested(MAX_DEPTH).unwrap().evaluate(), 7);
+        let error = nested(MAX_DEPTH + 1).err().unwrap();
+        assert!(error.expected.contains("nested"));
+    }
 }
 
 bench_day!(16);
@@ -1,29 +1,34 @@
-use crate::{day::Day, util::BitSet};
-use itertools::{iproduct, Itertools};
-use std::ops::RangeInclusive;
+use crate::{
+    day::Day,
+    util::{self, Cuboid, Cursor, Interval},
+};
 
-type R = RangeInclusive<i32>;
-
-fn observe_preimages<O: FnMut(R, R)>(xrange: &R, yrange: &R, mut observer: O) {
-    let (xstart, xend) = (*xrange.start(), *xrange.end());
-    let (ystart, yend) = (*yrange.start(), *yrange.end());
-    let t_inv = |x| ((-1.0 + (1.0 + 8.0 * x as f32).sqrt()) / 2.0);
-    (1..t_inv(xstart).ceil() as i32).for_each(|steps| {
+fn observe_preimages<O: FnMut(Interval, Interval)>(
+    xrange: Interval,
+    yrange: Interval,
+    mut observer: O,
+) {
+    let (xstart, xend) = (xrange.start, xrange.last());
+    let (ystart, yend) = (yrange.start, yrange.last());
+    let t_inv = |x| (-1.0 + (1.0 + 8.0 * x as f32).sqrt()) / 2.0;
+    (1..=t_inv(xend).floor() as i64).for_each(|steps| {
         let f_inv = |y| y as f32 / steps as f32 + (steps - 1) as f32 / 2.0;
+        // any slower and it would have stopped, which is dealt with below
+        let min_x = (f_inv(xstart).ceil() as i64).max(steps);
         observer(
-            f_inv(xstart).ceil() as i32..=f_inv(xend).floor() as i32,
-            f_inv(ystart).ceil() as i32..=f_inv(yend).floor() as i32,
+            Interval::closed(min_x, f_inv(xend).floor() as i64),
+            Interval::closed(f_inv(ystart).ceil() as i64, f_inv(yend).floor() as i64),
         );
     });
-    (t_inv(xstart).ceil() as i32..=t_inv(xend).floor() as i32).for_each(|steps| {
+    (t_inv(xstart).ceil() as i64..=t_inv(xend).floor() as i64).for_each(|steps| {
         let f_inv = |y| y as f32 / steps as f32 + (steps - 1) as f32 / 2.0;
-        for v in f_inv(ystart).ceil() as i32..=-ystart {
+        for v in f_inv(ystart).ceil() as i64..=-ystart {
             let enter_disc = (((2 * v + 1) * (2 * v + 1) - 8 * yend) as f32).sqrt();
             let exit_disc = (((2 * v + 1) * (2 * v + 1) - 8 * ystart) as f32).sqrt();
             if exit_disc - enter_disc >= 2.0
                 || ((1.0 + enter_disc) / 2.0).ceil() == ((1.0 + exit_disc) / 2.0).floor()
             {
-                observer(steps..=steps, v..=v);
+                observer(Interval::closed(steps, steps), Interval::closed(v, v));
             }
         }
     });
@@ -32,37 +37,38 @@ fn observe_preimages<O: FnMut(R, R)>(xrange: &R, yrange: &R, mut observer: O) {
 pub struct Day17;
 
 impl<'a> Day<'a> for Day17 {
-    type Input = (R, R);
-    type ProcessedInput = (R, R);
+    type Input = (Interval, Interval);
+    type ProcessedInput = Self::Input;
 
     const DAY: usize = 17;
 
     fn parse(input: &'a str) -> Self::Input {
-        let (xrange, yrange) = input.trim()[15..].split(", y=").next_tuple().unwrap();
-        let (xmin, xmax) = xrange.split("..").next_tuple().unwrap();
-        let (ymin, ymax) = yrange.split("..").next_tuple().unwrap();
-        (
-            xmin.parse().unwrap()..=xmax.parse().unwrap(),
-            ymin.parse().unwrap()..=ymax.parse().unwrap(),
-        )
+        let interval = |cursor: &mut Cursor| {
+            let (first, last) = cursor.range()?;
+            Ok(Interval::closed(first, last))
+        };
+        util::parse(input, |cursor| {
+            cursor.literal("target area: ")?;
+            let xrange = cursor.field("x", interval)?;
+            cursor.literal(", ")?;
+            Ok((xrange, cursor.field("y", interval)?))
+        })
+        .unwrap()
     }
 
     fn solve_part1((xrange, yrange): Self::Input) -> (Self::ProcessedInput, String) {
-        assert!(*xrange.start() > 0 && *yrange.end() < 0);
+        assert!(xrange.start > 0 && yrange.end <= 0);
         // assumes xrange contains a triangular number
-        let max_vel = -yrange.start() - 1;
+        let max_vel = -yrange.start - 1;
         let ans = max_vel * (max_vel + 1) / 2;
         ((xrange, yrange), ans.to_string())
     }
 
     fn solve_part2((xrange, yrange): Self::ProcessedInput) -> String {
-        let mut vels = BitSet::new();
-        let pack = |x, y| (x + (y - yrange.start()) * xrange.end()) as u32;
-        // I would love to sum up areas here, but sadly the preimages overlap...
-        observe_preimages(&xrange, &yrange, |rx, ry| {
-            iproduct!(rx, ry).for_each(|(x, y)| vels.insert(pack(x, y)))
-        });
-        vels.len().to_string()
+        // the preimages overlap, so it's the area of their union
+        let mut preimages = Vec::new();
+        observe_preimages(xrange, yrange, |rx, ry| preimages.push(Cuboid([rx, ry])));
+        util::union_volume(&preimages).to_string()
     }
 }
 
@@ -78,6 +84,62 @@ mod test_day17 {
         let part2 = Day17::solve_part2(input);
         assert_eq!(part2, "112");
     }
+
+    #[test]
+    fn test_day17_small_target() {
+        // shots still moving when they reach it count as well as those that
+        // stop inside it
+        let input = Day17::parse("target area: x=1..2, y=-1..-1");
+        let (input, part1) = Day17::solve_part1(input);
+        assert_eq!(part1, "0");
+        let part2 = Day17::solve_part2(input);
+        assert_eq!(part2, "3");
+    }
+
+    /// Fire every velocity that could possibly reach the target.
+    fn reference(input: &str) -> [String; 2] {
+        let (xrange, yrange) = Day17::parse(input);
+        let mut peaks = Vec::new();
+        for vx in 0..=xrange.last() {
+            for vy in yrange.start..=-yrange.start {
+                let (mut x, mut y, mut dx, mut dy, mut peak) = (0, 0, vx, vy, 0);
+                while x <= xrange.last() && y >= yrange.start {
+                    if xrange.contains(x) && yrange.contains(y) {
+                        peaks.push(peak);
+                        break;
+                    }
+                    x += dx;
+                    y += dy;
+                    dx -= dx.signum();
+                    dy -= 1;
+                    peak = peak.max(y);
+                }
+            }
+        }
+        [peaks.iter().max().unwrap().to_string(), peaks.len().to_string()]
+    }
+
+    #[test]
+    fn test_day17_against_reference() {
+        let generate = |random: &mut util::Random| {
+            let k = random.range(1..=12);
+            let x = k * (k + 1) / 2;
+            let y = random.range(-40..=-1);
+            let (x1, x2) = ((x - random.range(0..=5)).max(1), x + random.range(0..=10));
+            [x1, x2, y, y + random.range(0..=-1 - y)]
+        };
+        util::check(300, 17, generate, |&[x1, x2, y1, y2]| {
+            // like the puzzle's inputs, the target is below and to the right,
+            // and the highest shot can fall straight down onto it, having
+            // stopped over it by the time it comes back down past the start
+            let stops_over = (1..=-2 * y1).any(|k| (x1..=x2).contains(&(k * (k + 1) / 2)));
+            if !(1 <= x1 && x1 <= x2 && y1 <= y2 && y2 <= -1 && stops_over) {
+                return Ok(());
+            }
+            let input = format!("target area: x={}..{}, y={}..{}", x1, x2, y1, y2);
+            util::agree(&input, |input| Day17::solve(input), reference)
+        });
+    }
 }
 
 bench_day!(17);
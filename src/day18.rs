@@ -41,7 +41,7 @@ impl Pairs {
         let i = self.0.iter().position(|lit| lit.0 >= 10)?;
         let sp = self.0[i];
         self.0[i] = (sp.0 / 2, sp.1 + 1, sp.2);
-        self.0.insert(i + 1, ((sp.0 + 1) / 2, sp.1, sp.2 + 1));
+        self.0.insert(i + 1, (sp.0.div_ceil(2), sp.1, sp.2 + 1));
         Some(())
     }
 
@@ -81,6 +81,7 @@ impl<'a> Day<'a> for Day18 {
 #[cfg(test)]
 mod test_day18 {
     use super::*;
+    use crate::util;
     use indoc::indoc;
 
     const EXAMPLE: &str = indoc! {"
@@ -104,6 +105,157 @@ mod test_day18 {
         let part2 = Day18::solve_part2(input);
         assert_eq!(part2, "3993");
     }
+
+    /// A snailfish number as the tree it's written as.
+    #[derive(Clone, Debug)]
+    enum Snail {
+        Regular(u32),
+        Pair(Box<Snail>, Box<Snail>),
+    }
+
+    impl Snail {
+        fn parse(string: &mut &[u8]) -> Self {
+            let c = string[0];
+            *string = &string[1..];
+            if c != b'[' {
+                return Self::Regular((c - b'0') as u32);
+            }
+            let lhs = Self::parse(string);
+            *string = &string[1..];
+            let rhs = Self::parse(string);
+            *string = &string[1..];
+            Self::Pair(Box::new(lhs), Box::new(rhs))
+        }
+
+        fn add_leftmost(&mut self, n: u32) {
+            match self {
+                Self::Regular(m) => *m += n,
+                Self::Pair(lhs, _) => lhs.add_leftmost(n),
+            }
+        }
+
+        fn add_rightmost(&mut self, n: u32) {
+            match self {
+                Self::Regular(m) => *m += n,
+                Self::Pair(_, rhs) => rhs.add_rightmost(n),
+            }
+        }
+
+        /// Explode the leftmost pair nested inside four others, returning
+        /// what it leaves to be added to its neighbours on each side.
+        fn explode(&mut self, depth: usize) -> Option<(u32, u32)> {
+            let Self::Pair(lhs, rhs) = self else {
+                return None;
+            };
+            if depth == 4 {
+                let sides = match (&**lhs, &**rhs) {
+                    (Self::Regular(l), Self::Regular(r)) => (*l, *r),
+                    _ => unreachable!("pairs are never nested five deep"),
+                };
+                *self = Self::Regular(0);
+                return Some(sides);
+            }
+            if let Some((l, r)) = lhs.explode(depth + 1) {
+                rhs.add_leftmost(r);
+                Some((l, 0))
+            } else {
+                let (l, r) = rhs.explode(depth + 1)?;
+                lhs.add_rightmost(l);
+                Some((0, r))
+            }
+        }
+
+        fn split(&mut self) -> bool {
+            match self {
+                Self::Regular(n) if *n >= 10 => {
+                    let halves = (Self::Regular(*n / 2), Self::Regular(n.div_ceil(2)));
+                    *self = Self::Pair(Box::new(halves.0), Box::new(halves.1));
+                    true
+                }
+                Self::Regular(_) => false,
+                Self::Pair(lhs, rhs) => lhs.split() || rhs.split(),
+            }
+        }
+
+        fn add(self, other: Self) -> Self {
+            let mut sum = Self::Pair(Box::new(self), Box::new(other));
+            while sum.explode(0).is_some() || sum.split() {}
+            sum
+        }
+
+        fn magnitude(&self) -> u32 {
+            match self {
+                Self::Regular(n) => *n,
+                Self::Pair(lhs, rhs) => 3 * lhs.magnitude() + 2 * rhs.magnitude(),
+            }
+        }
+    }
+
+    fn reference(input: &str) -> [String; 2] {
+        let numbers = input
+            .lines()
+            .map(|line| Snail::parse(&mut line.as_bytes()))
+            .collect::<Vec<_>>();
+        let sum = numbers.iter().cloned().reduce(Snail::add).unwrap();
+        let mut largest = 0;
+        for (i, lhs) in numbers.iter().enumerate() {
+            for (j, rhs) in numbers.iter().enumerate() {
+                if i != j {
+                    largest = largest.max(lhs.clone().add(rhs.clone()).magnitude());
+                }
+            }
+        }
+        [sum.magnitude().to_string(), largest.to_string()]
+    }
+
+    /// Write out the number `tokens` spell in prefix order, where a token
+    /// of 10 or more opens a pair and anything else is a regular number.
+    /// Any tokens will do: missing ones are zeros, extra ones are ignored,
+    /// and pairs too deep are cut short, so shrinking can't make a number
+    /// the puzzle wouldn't give.
+    fn write_number(tokens: &[u8]) -> String {
+        fn write(tokens: &mut impl Iterator<Item = u8>, depth: usize, out: &mut String) {
+            match tokens.next().unwrap_or(0) {
+                n if n < 10 || depth == 4 => out.push((b'0' + n % 10) as char),
+                _ => {
+                    out.push('[');
+                    write(tokens, depth + 1, out);
+                    out.push(',');
+                    write(tokens, depth + 1, out);
+                    out.push(']');
+                }
+            }
+        }
+        let mut tokens = tokens.iter().copied();
+        let mut out = String::from("[");
+        write(&mut tokens, 1, &mut out);
+        out.push(',');
+        write(&mut tokens, 1, &mut out);
+        out.push(']');
+        out
+    }
+
+    #[test]
+    fn test_day18_against_reference() {
+        let generate = |random: &mut util::Random| {
+            let count = random.range(2..=5);
+            let token = |random: &mut util::Random| match random.below(5) {
+                0 | 1 => 10,
+                _ => random.below(10) as u8,
+            };
+            (0..count)
+                .map(|_| (0..30).map(|_| token(random)).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        };
+        util::check(200, 18, generate, |numbers| {
+            if numbers.len() < 2 {
+                return Ok(());
+            }
+            let lines = numbers.iter().map(|tokens| write_number(tokens));
+            let input = lines.collect::<Vec<_>>().join("\n");
+            util::agree(&input, |input| Day18::solve(input), reference)
+        });
+    }
 }
 
 bench_day!(18);
@@ -1,53 +1,37 @@
-use crate::{day::Day, util::SortedVec};
-use hashbrown::HashSet;
+use crate::{
+    day::Day,
+    util::{self, Cursor, Rotation3, SortedVec, Vec3},
+};
 use itertools::Itertools;
-use std::cmp::Ordering;
 
 const THRESHOLD: usize = 6; // this is heuristic
 
-type Point = [i16; 3];
-type CanonicalPoint = [i16; 3];
+type Point = Vec3<i32>;
+type CanonicalPoint = Vec3<i32>;
 type ScanCanonicalization = SortedVec<(CanonicalPoint, (u8, u8))>;
 
-fn sort3_by_abs(ns: &mut [i16; 3]) -> i16 {
-    let mut ms = ns.map(i16::abs);
-    let mut sign = 1;
-    if ms[0] > ms[1] {
-        ns.swap(0, 1);
-        ms.swap(0, 1);
-        sign *= -1;
-    }
-    if ms[0] > ms[2] {
-        ns.swap(0, 2);
-        ms.swap(0, 2);
-        sign *= -1;
-    }
-    if ms[1] > ms[2] {
-        ns.swap(1, 2);
-        ms.swap(1, 2);
-        sign *= -1;
-    }
-    sign
-}
-
-fn canonicalize(mut p: Point) -> CanonicalPoint {
-    let sign = sort3_by_abs(&mut p) * p[0].signum() * p[1].signum();
-    [p[0].abs(), p[1].abs(), sign * p[2]]
+/// The same point for every rotation of `p`: the least of its rotations.
+fn canonicalize(p: Point) -> CanonicalPoint {
+    Rotation3::all().map(|rotation| rotation * p).min().unwrap()
 }
 
+/// Whether only one rotation takes one vector with this shape to another.
 fn is_skew(p: CanonicalPoint) -> bool {
-    0 != p[0] && p[0] != p[1] && p[1] != p[2].abs()
+    let [x, y, z] = <[i32; 3]>::from(p).map(i32::abs);
+    x != 0 && y != 0 && z != 0 && x != y && y != z && x != z
 }
 
 fn shape_of(scan: &[Point]) -> ScanCanonicalization {
-    let mut shape = SortedVec::with_capacity(scan.len() * (scan.len() - 1));
-    for ((i, [ix, iy, iz]), (j, [jx, jy, jz])) in scan.iter().enumerate().tuple_combinations() {
-        let connection = canonicalize([ix - jx, iy - jy, iz - jz]);
-        let flipped_connection = [connection[0], connection[1], -connection[2]];
-        shape.push((connection, (i as u8, j as u8)));
-        shape.push((flipped_connection, (j as u8, i as u8)));
-    }
-    shape
+    scan.iter()
+        .enumerate()
+        .tuple_combinations()
+        .flat_map(|((i, &p), (j, &q))| {
+            [
+                (canonicalize(p - q), (i as u8, j as u8)),
+                (canonicalize(q - p), (j as u8, i as u8)),
+            ]
+        })
+        .collect()
 }
 
 fn find_map_matches<R, F: FnMut(CanonicalPoint, (u8, u8), (u8, u8)) -> Option<R>>(
@@ -55,30 +39,15 @@ fn find_map_matches<R, F: FnMut(CanonicalPoint, (u8, u8), (u8, u8)) -> Option<R>
     rhs: &ScanCanonicalization,
     mut f: F,
 ) -> Option<R> {
-    let (mut lhs_conns, mut rhs_conns) = (lhs.iter().peekable(), rhs.iter().peekable());
-    while let (Some(&&(lhs_conn, lhs_idxs)), Some(&&(rhs_conn, rhs_idxs))) =
-        (lhs_conns.peek(), rhs_conns.peek())
-    {
-        match lhs_conn.cmp(&rhs_conn) {
-            Ordering::Less => lhs_conns.next(),
-            Ordering::Greater => rhs_conns.next(),
-            Ordering::Equal => {
-                if let Some(result) = f(lhs_conn, lhs_idxs, rhs_idxs) {
-                    return Some(result);
-                }
-                lhs_conns.next();
-                rhs_conns.next()
-            }
-        };
-    }
-    None
+    lhs.intersection_by(rhs, |(lhs_conn, _), (rhs_conn, _)| lhs_conn.cmp(rhs_conn))
+        .find_map(|(&(conn, lhs_idxs), &(_, rhs_idxs))| f(conn, lhs_idxs, rhs_idxs))
 }
 
 fn shapes_match(lhs: &ScanCanonicalization, rhs: &ScanCanonicalization) -> bool {
     let mut matches = 0;
     find_map_matches(lhs, rhs, |_, _, _| {
         matches += 1;
-        (matches == THRESHOLD).then(|| ())
+        (matches == THRESHOLD).then_some(())
     })
     .is_some()
 }
@@ -88,35 +57,24 @@ fn find_skew(
     rhs: &ScanCanonicalization,
 ) -> Option<((u8, u8), (u8, u8))> {
     find_map_matches(lhs, rhs, |conn, onto_idxs, from_idxs| {
-        is_skew(conn).then(|| (onto_idxs, from_idxs))
+        is_skew(conn).then_some((onto_idxs, from_idxs))
     })
 }
 
-fn orient(tv: Point, tp: Point, fv: Point, fp: Point, from: &[Point]) -> (Point, Vec<Point>) {
-    let ind = |n| match n {
-        _ if n == fv[0].abs() => 0,
-        _ if n == fv[1].abs() => 1,
-        _ => 2,
-    };
-    let pm = [ind(tv[0].abs()), ind(tv[1].abs()), ind(tv[2].abs())];
-    let sign = [tv[0] / fv[pm[0]], tv[1] / fv[pm[1]], tv[2] / fv[pm[2]]];
-    let offset = [
-        tp[0] - sign[0] * fp[pm[0]],
-        tp[1] - sign[1] * fp[pm[1]],
-        tp[2] - sign[2] * fp[pm[2]],
-    ];
-    (
-        offset,
-        from.iter()
-            .map(|point| {
-                [
-                    offset[0] + sign[0] * point[pm[0]],
-                    offset[1] + sign[1] * point[pm[1]],
-                    offset[2] + sign[2] * point[pm[2]],
-                ]
-            })
-            .collect(),
-    )
+/// Rotate and translate `from` so that `from_vec` and `from_point` land on
+/// `onto_vec` and `onto_point`, returning the translation and the points.
+fn orient(
+    onto_vec: Point,
+    onto_point: Point,
+    from_vec: Point,
+    from_point: Point,
+    from: &[Point],
+) -> (Point, Vec<Point>) {
+    let rotation = Rotation3::all()
+        .find(|&rotation| rotation * from_vec == onto_vec)
+        .unwrap();
+    let offset = onto_point - rotation * from_point;
+    (offset, from.iter().map(|&p| rotation * p + offset).collect())
 }
 
 pub struct Day19;
@@ -128,14 +86,21 @@ impl<'a> Day<'a> for Day19 {
     const DAY: usize = 19;
 
     fn parse(input: &'a str) -> Self::Input {
-        let parse_point = |point| {
-            str::split(point, ',')
-                .next_tuple()
-                .and_then(|(x, y, z)| Some([x.parse().ok()?, y.parse().ok()?, z.parse().ok()?]))
-                .unwrap()
+        let parse_point = |cursor: &mut Cursor| {
+            match cursor.separated(",", Cursor::int)?[..] {
+                [x, y, z] => Ok(Vec3::new(x, y, z)),
+                _ => Err(cursor.error("three coordinates")),
+            }
         };
-        let parse_scan = |scan| str::lines(scan).skip(1).map(parse_point).collect();
-        input.split("\n\n").map(parse_scan).collect()
+        util::parse(input, |cursor| {
+            cursor.sections(|cursor| {
+                cursor.literal("--- scanner ")?;
+                cursor.int::<usize>()?;
+                cursor.literal(" ---\n")?;
+                cursor.lines(parse_point)
+            })
+        })
+        .unwrap()
     }
 
     fn solve_part1(scans: Self::Input) -> (Self::ProcessedInput, String) {
@@ -143,7 +108,7 @@ impl<'a> Day<'a> for Day19 {
         let mut oriented = vec![Vec::with_capacity(scans[0].len()); scans.len()];
         let mut offsets = Vec::with_capacity(scans.len());
         oriented[0] = scans[0].clone();
-        offsets.push([0, 0, 0]);
+        offsets.push(Vec3::default());
         while oriented.iter().any(Vec::is_empty) {
             for i in 0..scans.len() {
                 if oriented[i].is_empty() {
@@ -162,32 +127,24 @@ impl<'a> Day<'a> for Day19 {
                             (scan_i[lhs_idx_i as usize], scan_i[rhs_idx_i as usize]);
                         let (lhs_j, rhs_j) =
                             (scan_j[lhs_idx_j as usize], scan_j[rhs_idx_j as usize]);
-                        let vec_i = [
-                            lhs_i[0] - rhs_i[0],
-                            lhs_i[1] - rhs_i[1],
-                            lhs_i[2] - rhs_i[2],
-                        ];
-                        let vec_j = [
-                            lhs_j[0] - rhs_j[0],
-                            lhs_j[1] - rhs_j[1],
-                            lhs_j[2] - rhs_j[2],
-                        ];
-                        let (offset, oriented_j) = orient(vec_i, lhs_i, vec_j, lhs_j, scan_j);
+                        let (offset, oriented_j) =
+                            orient(lhs_i - rhs_i, lhs_i, lhs_j - rhs_j, lhs_j, scan_j);
                         oriented[j] = oriented_j;
                         offsets.push(offset);
                     }
                 }
             }
         }
-        let ans = oriented.into_iter().flatten().collect::<HashSet<_>>().len();
-        (offsets, ans.to_string())
+        let mut beacons = oriented.into_iter().flatten().collect::<SortedVec<_>>();
+        beacons.dedup();
+        (offsets, beacons.len().to_string())
     }
 
     fn solve_part2(offsets: Self::ProcessedInput) -> String {
         offsets
             .into_iter()
             .tuple_combinations()
-            .map(|(l, r)| l[0].abs_diff(r[0]) + l[1].abs_diff(r[1]) + l[2].abs_diff(r[2]))
+            .map(|(l, r)| (l - r).manhattan())
             .max()
             .unwrap()
             .to_string()
@@ -1,7 +1,8 @@
-use crate::day::Day;
-use hashbrown::HashMap;
-use itertools::Itertools;
-use std::{hash::Hash, ops::AddAssign};
+use crate::{
+    day::Day,
+    util::{self, BigUint, Cursor, Memo, Scalar},
+};
+use std::{hash::Hash, mem, ops::AddAssign};
 
 const DIRAC_ROLL_SUMS: [(u8, u64); 7] = [(3, 1), (4, 3), (5, 6), (6, 7), (7, 6), (8, 3), (9, 1)];
 
@@ -41,37 +42,42 @@ where
     }
 }
 
-fn dirac_wins_from<S: Copy + Eq + Hash + State>(state: S) -> [u64; 2]
+fn dirac_wins_from<S, T>(state: S) -> Option<[T; 2]>
 where
+    S: Copy + Eq + Hash + State,
     <S as State>::Score: Copy + Ord + AddAssign + From<u8>,
+    T: Scalar + From<u64>,
 {
-    fn dirac_wins_from_memo<S: Copy + Eq + Hash + State>(
-        state: S,
-        memo: &mut HashMap<S, [u64; 2]>,
-    ) -> [u64; 2]
-    where
-        <S as State>::Score: Copy + Ord + AddAssign + From<u8>,
-    {
-        match memo.get(&state) {
-            Some(&wins) => wins,
-            None => {
-                let mut wins = [0, 0];
-                for &(roll_sum, universes) in DIRAC_ROLL_SUMS.iter() {
-                    let then_wins = match play(roll_sum, 21.into(), state) {
-                        Ok(state) => dirac_wins_from_memo(state, memo),
-                        Err(_) => [(1 - state.player()) as u64, state.player() as u64],
-                    };
-                    wins[0] += universes * then_wins[0];
-                    wins[1] += universes * then_wins[1];
-                }
-                memo.insert(state, wins);
-                wins
+    let wins_from = |state: S, recurse: &mut dyn FnMut(S) -> Option<[T; 2]>| {
+        let mut wins = [T::zero(), T::zero()];
+        for &(roll_sum, universes) in DIRAC_ROLL_SUMS.iter() {
+            let then_wins = match play(roll_sum, 21.into(), state) {
+                Ok(state) => recurse(state)?,
+                Err(_) => [1 - state.player(), state.player()].map(|n| T::from(n as u64)),
+            };
+            for (total, then) in wins.iter_mut().zip(then_wins) {
+                let then = T::from(universes).checked_mul(then)?;
+                *total = mem::replace(total, T::zero()).checked_add(then)?;
             }
         }
-    }
+        Some(wins)
+    };
     // the total number of possible states is 2*10*10*21*21 = 88200, and any
     // pair of starting positions reaches between 20000 to 30000 of these.
-    dirac_wins_from_memo(state, &mut HashMap::with_capacity(30000))
+    Memo::with_capacity(30000).get(state, &wins_from)
+}
+
+/// The number of universes the likelier winner wins in, falling back to a
+/// `BigUint` if that doesn't fit in a `u64`.
+fn most_dirac_wins(positions: [u8; 2]) -> String {
+    let state = DiracState::with_positions(positions);
+    match dirac_wins_from::<_, u64>(state) {
+        Some(wins) => wins.iter().max().unwrap().to_string(),
+        None => {
+            let wins = dirac_wins_from::<_, BigUint>(state).unwrap();
+            wins.iter().max().unwrap().to_string()
+        }
+    }
 }
 
 pub struct Day21;
@@ -83,13 +89,25 @@ impl<'a> Day<'a> for Day21 {
     const DAY: usize = 21;
 
     fn parse(input: &'a str) -> Self::Input {
-        let (p1, p2) = input.lines().next_tuple().unwrap();
-        let (p1, p2): (u8, u8) = (p1[28..].parse().unwrap(), p2[28..].parse().unwrap());
-        [p1 - 1, p2 - 1]
+        let (p1, p2) = util::parse(input, |cursor| {
+            let starting_position = |cursor: &mut Cursor, player| {
+                cursor.literal(&format!("Player {} starting position: ", player))?;
+                let position = cursor.int::<u8>()?;
+                match (1..=10).contains(&position) {
+                    true => Ok(position - 1),
+                    false => Err(cursor.error("a position from 1 to 10")),
+                }
+            };
+            let p1 = starting_position(cursor, 1)?;
+            cursor.literal("\n")?;
+            Ok((p1, starting_position(cursor, 2)?))
+        })
+        .unwrap();
+        [p1, p2]
     }
 
     fn solve_part1(positions: Self::Input) -> (Self::ProcessedInput, String) {
-        let mut state = u64::with_positions(positions);
+        let mut state = PracticeState::with_positions(positions);
         let roll_sums = (0..10).rev().cycle().skip(3);
         let roll_counts = (3..).step_by(3);
         for (roll_count, roll_sum) in roll_counts.zip(roll_sums) {
@@ -102,82 +120,71 @@ impl<'a> Day<'a> for Day21 {
     }
 
     fn solve_part2(positions: Self::ProcessedInput) -> String {
-        dirac_wins_from(u32::with_positions(positions))
-            .iter()
-            .max()
-            .unwrap()
-            .to_string()
+        most_dirac_wins(positions)
     }
 }
 
-impl State for u64 {
-    type Score = u16;
-
-    fn with_positions(positions: [u8; 2]) -> Self {
-        (positions[0] as Self) << 8 | (positions[1] as Self) << 12
-    }
-
-    fn player(&self) -> u8 {
-        (self & 1) as u8
-    }
-
-    fn swap_players(&mut self) {
-        *self ^= 1;
-    }
-
-    fn score(&self, player: u8) -> Self::Score {
-        ((self >> (32 + 16 * player)) & 0xffff) as Self::Score
-    }
-
-    fn set_score(&mut self, player: u8, score: Self::Score) {
-        *self &= !(0xffff << (32 + 16 * player));
-        *self |= (score as Self) << (32 + 16 * player);
-    }
-
-    fn position(&self, player: u8) -> u8 {
-        ((self >> (8 + 4 * player)) & 0xf) as u8
-    }
-
-    fn set_position(&mut self, player: u8, position: u8) {
-        *self &= !(0xf << (8 + 4 * player));
-        *self |= (position as Self) << (8 + 4 * player);
-    }
-}
+macro_rules! state {
+    ($(#[$attr:meta])* $name:ident($repr:ty), $score:ty, $score_bits:literal) => {
+        crate::packed! {
+            $(#[$attr])*
+            struct $name($repr) {
+                turn: u8 = 1,
+                positions: [u8; 2] = 4,
+                scores: [$score; 2] = $score_bits,
+            }
+        }
 
-impl State for u32 {
-    type Score = u8;
+        impl State for $name {
+            type Score = $score;
 
-    fn with_positions(positions: [u8; 2]) -> Self {
-        (positions[0] as Self) << 8 | (positions[1] as Self) << 12
-    }
+            fn with_positions(positions: [u8; 2]) -> Self {
+                let mut state = Self::default();
+                state.set_positions(0, positions[0]);
+                state.set_positions(1, positions[1]);
+                state
+            }
 
-    fn player(&self) -> u8 {
-        (self & 1) as u8
-    }
+            fn player(&self) -> u8 {
+                self.turn()
+            }
 
-    fn swap_players(&mut self) {
-        *self ^= 1;
-    }
+            fn swap_players(&mut self) {
+                self.set_turn(1 - self.turn());
+            }
 
-    fn score(&self, player: u8) -> Self::Score {
-        ((self >> (16 + 8 * player)) & 0xff) as Self::Score
-    }
+            fn score(&self, player: u8) -> Self::Score {
+                self.scores(player as usize)
+            }
 
-    fn set_score(&mut self, player: u8, score: Self::Score) {
-        *self &= !(0xff << (16 + 8 * player));
-        *self |= (score as Self) << (16 + 8 * player);
-    }
+            fn set_score(&mut self, player: u8, score: Self::Score) {
+                self.set_scores(player as usize, score);
+            }
 
-    fn position(&self, player: u8) -> u8 {
-        ((self >> (8 + 4 * player)) & 0xf) as u8
-    }
+            fn position(&self, player: u8) -> u8 {
+                self.positions(player as usize)
+            }
 
-    fn set_position(&mut self, player: u8, position: u8) {
-        *self &= !(0xf << (8 + 4 * player));
-        *self |= (position as Self) << (8 + 4 * player);
-    }
+            fn set_position(&mut self, player: u8, position: u8) {
+                self.set_positions(player as usize, position);
+            }
+        }
+    };
 }
 
+state!(
+    /// A game to 1000 points.
+    PracticeState(u64),
+    u16,
+    16
+);
+state!(
+    /// A game to 21 points, small so the memo is cheap.
+    DiracState(u32),
+    u8,
+    8
+);
+
 #[cfg(test)]
 mod test_day21 {
     use super::*;
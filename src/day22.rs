@@ -1,14 +1,13 @@
-use crate::day::Day;
+use crate::{
+    day::Day,
+    util::{self, Cuboid, Cursor, Interval},
+};
 use hashbrown::HashMap;
-use itertools::Itertools;
 
-type Interval = (i32, i32); // inclusive start, exclusive end
-type Cuboid = (Interval, Interval, Interval);
-
-fn remove(region: Cuboid, from: &mut HashMap<Cuboid, i32>) {
+fn remove(region: Cuboid<3>, from: &mut HashMap<Cuboid<3>, i32>) {
     let mut new = Vec::new();
     for (&cuboid, coeff) in from.iter_mut() {
-        match cuboid_intersection(region, cuboid) {
+        match region.intersection(&cuboid) {
             Some(x) if x == cuboid => *coeff = i32::MAX,
             Some(x) => new.push((x, -*coeff)),
             None => {}
@@ -19,7 +18,7 @@ fn remove(region: Cuboid, from: &mut HashMap<Cuboid, i32>) {
         .for_each(|(cuboid, coeff)| *from.entry(cuboid).or_insert(0) += coeff);
 }
 
-fn count_after(instructions: impl Iterator<Item = (bool, Cuboid)>) -> i64 {
+fn count_after(instructions: impl Iterator<Item = (bool, Cuboid<3>)>) -> i64 {
     let mut sum = HashMap::new();
     for (flag, region) in instructions {
         remove(region, &mut sum);
@@ -28,41 +27,43 @@ fn count_after(instructions: impl Iterator<Item = (bool, Cuboid)>) -> i64 {
         }
     }
     sum.into_iter()
-        .map(|(cuboid, coeff)| coeff as i64 * volume(cuboid))
+        .map(|(cuboid, coeff)| coeff as i64 * cuboid.volume())
         .sum()
 }
 
 pub struct Day22;
 
 impl<'a> Day<'a> for Day22 {
-    type Input = Vec<(bool, Cuboid)>;
+    type Input = Vec<(bool, Cuboid<3>)>;
     type ProcessedInput = Self::Input;
 
     const DAY: usize = 22;
 
     fn parse(input: &'a str) -> Self::Input {
-        input
-            .lines()
-            .map(|line| {
-                let (flag, cuboid) = line.split_once(' ').unwrap();
-                let (x, y, z) = cuboid.split(',').next_tuple().unwrap();
-                let (sx, ex) = x[2..].split("..").next_tuple().unwrap();
-                let (sy, ey) = y[2..].split("..").next_tuple().unwrap();
-                let (sz, ez) = z[2..].split("..").next_tuple().unwrap();
-                let xr = (sx.parse().unwrap(), ex.parse::<i32>().unwrap() + 1);
-                let yr = (sy.parse().unwrap(), ey.parse::<i32>().unwrap() + 1);
-                let zr = (sz.parse().unwrap(), ez.parse::<i32>().unwrap() + 1);
-                (flag == "on", (xr, yr, zr))
+        let interval = |cursor: &mut Cursor| {
+            let (first, last) = cursor.range()?;
+            Ok(Interval::closed(first, last))
+        };
+        util::parse(input, |cursor| {
+            cursor.lines(|cursor| {
+                let on = cursor.eat("on ") || cursor.literal("off ").map(|_| false)?;
+                let x = cursor.field("x", interval)?;
+                cursor.literal(",")?;
+                let y = cursor.field("y", interval)?;
+                cursor.literal(",")?;
+                Ok((on, Cuboid([x, y, cursor.field("z", interval)?])))
             })
-            .collect()
+        })
+        .unwrap()
     }
 
     fn solve_part1(instructions: Self::Input) -> (Self::ProcessedInput, String) {
-        let init_region = ((-50, 51), (-50, 51), (-50, 51));
-        let ans =
-            count_after(instructions.iter().filter_map(|&(flag, region)| {
-                Some((flag, cuboid_intersection(region, init_region)?))
-            }));
+        let init_region = Cuboid([Interval::closed(-50, 50); 3]);
+        let ans = count_after(
+            instructions
+                .iter()
+                .filter_map(|&(flag, region)| Some((flag, region.intersection(&init_region)?))),
+        );
         (instructions, ans.to_string())
     }
 
@@ -71,27 +72,6 @@ impl<'a> Day<'a> for Day22 {
     }
 }
 
-fn length(interval: Interval) -> i64 {
-    (interval.1 - interval.0) as i64
-}
-
-fn volume(cuboid: Cuboid) -> i64 {
-    length(cuboid.0) * length(cuboid.1) * length(cuboid.2)
-}
-
-fn interval_intersection(lhs: Interval, rhs: Interval) -> Option<Interval> {
-    let x = (lhs.0.max(rhs.0), lhs.1.min(rhs.1));
-    (x.0 < x.1).then(|| x)
-}
-
-fn cuboid_intersection(lhs: Cuboid, rhs: Cuboid) -> Option<Cuboid> {
-    Some((
-        interval_intersection(lhs.0, rhs.0)?,
-        interval_intersection(lhs.1, rhs.1)?,
-        interval_intersection(lhs.2, rhs.2)?,
-    ))
-}
-
 #[cfg(test)]
 mod test_day22 {
     use super::*;
@@ -190,6 +170,78 @@ mod test_day22 {
         assert_eq!(Day22::solve_part1(Day22::parse(EX1)).1, "590784");
         assert_eq!(Day22::solve_part2(Day22::parse(EX2)), "2758514936282235");
     }
+
+    /// Cut space up along every face, and switch whole blocks on and off.
+    fn lit_after(instructions: &[(bool, Cuboid<3>)]) -> i64 {
+        let cuts = (0..3)
+            .map(|axis| {
+                let mut cuts = instructions
+                    .iter()
+                    .flat_map(|(_, region)| [region.0[axis].start, region.0[axis].last() + 1])
+                    .collect::<Vec<_>>();
+                cuts.sort_unstable();
+                cuts.dedup();
+                cuts
+            })
+            .collect::<Vec<_>>();
+        let block = |axis: usize, i: usize| Interval::closed(cuts[axis][i], cuts[axis][i + 1] - 1);
+        let mut lit = 0;
+        for x in 0..cuts[0].len().saturating_sub(1) {
+            for y in 0..cuts[1].len() - 1 {
+                for z in 0..cuts[2].len() - 1 {
+                    let block = Cuboid([block(0, x), block(1, y), block(2, z)]);
+                    let corner = [block.0[0].start, block.0[1].start, block.0[2].start];
+                    let last = instructions.iter().rev().find(|(_, r)| r.contains(corner));
+                    if let Some((true, _)) = last {
+                        lit += block.volume();
+                    }
+                }
+            }
+        }
+        lit
+    }
+
+    fn reference(input: &str) -> [String; 2] {
+        let instructions = Day22::parse(input);
+        let init_region = Cuboid([Interval::closed(-50, 50); 3]);
+        let initialization = instructions
+            .iter()
+            .filter_map(|&(on, region)| Some((on, region.intersection(&init_region)?)))
+            .collect::<Vec<_>>();
+        [
+            lit_after(&initialization).to_string(),
+            lit_after(&instructions).to_string(),
+        ]
+    }
+
+    #[test]
+    fn test_day22_against_reference() {
+        let generate = |random: &mut util::Random| {
+            let count = random.range(1..=12);
+            let step = |random: &mut util::Random| {
+                let mut bounds = [0; 6];
+                bounds.iter_mut().for_each(|b| *b = random.range(-60..=60));
+                (random.below(3) != 0, bounds)
+            };
+            (0..count).map(|_| step(random)).collect::<Vec<_>>()
+        };
+        util::check(200, 22, generate, |steps| {
+            let lines = steps.iter().map(|(on, [x1, x2, y1, y2, z1, z2])| {
+                format!(
+                    "{} x={}..{},y={}..{},z={}..{}",
+                    if *on { "on" } else { "off" },
+                    x1.min(x2),
+                    x1.max(x2),
+                    y1.min(y2),
+                    y1.max(y2),
+                    z1.min(z2),
+                    z1.max(z2)
+                )
+            });
+            let input = lines.collect::<Vec<_>>().join("\n");
+            util::agree(&input, |input| Day22::solve(input), reference)
+        });
+    }
 }
 
 bench_day!(22);
@@ -1,8 +1,7 @@
 use crate::{
     day::Day,
-    util::{self, CollectArray},
+    util::{self, Cursor, ParseResult},
 };
-use itertools::Itertools;
 
 pub struct Day23;
 
@@ -13,9 +12,25 @@ impl<'a> Day<'a> for Day23 {
     const DAY: usize = 23;
 
     fn parse(input: &'a str) -> Self::Input {
-        let (outer, inner) = input.lines().skip(2).next_tuple().unwrap();
-        let line_chars = |line: &str| line[3..].chars().step_by(2).take(4).collect_array();
-        [outer, inner].map(line_chars).map(DoorLayer::from_chars)
+        let layer = |cursor: &mut Cursor<'a>| -> ParseResult<_> {
+            let mut amphipods = ['A'; 4];
+            for (i, amphipod) in amphipods.iter_mut().enumerate() {
+                if i > 0 {
+                    cursor.literal("#")?;
+                }
+                *amphipod = cursor.one_of("ABCD")?;
+            }
+            Ok(DoorLayer::from_chars(amphipods))
+        };
+        util::parse(input, |cursor| {
+            cursor.literal("#############\n#...........#\n###")?;
+            let outer = layer(cursor)?;
+            cursor.literal("###\n  #")?;
+            let inner = layer(cursor)?;
+            cursor.literal("#\n  #########")?;
+            Ok([outer, inner])
+        })
+        .unwrap()
     }
 
     fn solve_part1(doors: Self::Input) -> (Self::ProcessedInput, String) {
@@ -23,9 +38,7 @@ impl<'a> Day<'a> for Day23 {
             hall: Hall::default(),
             doors,
         };
-        let ans = util::dijkstra(initial_state, State::iter_moves, State::is_goal).unwrap()
-            + initial_state.heuristic();
-        (doors, ans.to_string())
+        (doors, State::min_energy(initial_state).to_string())
     }
 
     fn solve_part2(doors: Self::ProcessedInput) -> String {
@@ -38,9 +51,7 @@ impl<'a> Day<'a> for Day23 {
                 doors[1],
             ],
         };
-        (util::dijkstra(initial_state, State::iter_moves, State::is_goal).unwrap()
-            + initial_state.heuristic())
-        .to_string()
+        State::min_energy(initial_state).to_string()
     }
 }
 
@@ -59,6 +70,14 @@ impl<const DEPTH: usize> State<DEPTH> {
         (0..4).all(|door| (0..DEPTH).all(|layer| self.doors[layer].get(door) == Some(door + 1)))
     }
 
+    fn min_energy(self) -> usize {
+        util::astar(self, Self::iter_moves, Self::is_goal, Self::heuristic)
+            .unwrap()
+            .cost
+    }
+
+    /// The energy still needed to step down into the rooms, which every
+    /// amphipod not yet settled at the bottom of its room will have to spend.
     fn heuristic(self) -> usize {
         1111 * (DEPTH * (DEPTH + 3) / 2)
             - (0..4)
@@ -79,7 +98,7 @@ impl<const DEPTH: usize> State<DEPTH> {
             .last()?;
         (layer + 1..DEPTH)
             .all(|l| self.doors[l].get(index) == Some(mover))
-            .then(|| layer)
+            .then_some(layer)
     }
 
     fn iter_moves(self) -> impl Iterator<Item = (usize, Self)> {
@@ -93,9 +112,9 @@ impl<const DEPTH: usize> State<DEPTH> {
             let dist = self.hall.try_move(h, enter_at)?;
             let layer = self.try_enter_door(occ - 1, occ)?;
             let mut next_state = self;
-            next_state.hall.set(h, 0);
-            next_state.doors[layer].set(occ - 1, occ);
-            Some((Self::movement_cost(occ) * dist, next_state))
+            next_state.hall.set_places(h as usize, 0);
+            next_state.doors[layer].set_rooms((occ - 1) as usize, occ);
+            Some((Self::movement_cost(occ) * (dist + layer + 2), next_state))
         })
     }
 
@@ -118,8 +137,8 @@ impl<const DEPTH: usize> State<DEPTH> {
                     .map(move |h| (h, layer + 2 * (h - 1 - door) as usize - usize::from(h == 6)));
                 Some(lefts.chain(rights).map(move |(h, dist)| {
                     let mut next_state = self;
-                    next_state.doors[layer].set(door, 0);
-                    next_state.hall.set(h, occ);
+                    next_state.doors[layer].set_rooms(door as usize, 0);
+                    next_state.hall.set_places(h as usize, occ);
                     (Self::movement_cost(occ) * dist, next_state)
                 }))
             })
@@ -127,56 +146,52 @@ impl<const DEPTH: usize> State<DEPTH> {
     }
 }
 
-#[derive(Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-struct Hall(u32);
-
-impl Hall {
-    fn set(&mut self, index: u8, occ: u8) {
-        self.0 &= !(0b1111 << (4 * index));
-        self.0 |= (occ as u32) << (4 * index);
+crate::packed! {
+    /// The amphipod stopped at each of the 7 places in the hall, or 0.
+    struct Hall(u32) {
+        places: [u8; 7] = 4,
     }
+}
 
+impl Hall {
     fn get(self, index: u8) -> Option<u8> {
-        let occ = ((self.0 >> (4 * index)) & 0b1111) as u8;
-        (occ != 0).then(|| occ)
+        let occ = self.places(index as usize);
+        (occ != 0).then_some(occ)
     }
 
     fn try_move(self, from: u8, to: u8) -> Option<usize> {
-        let delta = to.abs_diff(from);
-        let shift = 4 * to.min(from + 1);
-        let mask = (1 << (4 * delta)) - 1;
+        let delta = to.abs_diff(from) as usize;
+        // the places passed through, not counting the one moved from
+        let start = to.min(from + 1) as usize;
         let edge_cases = usize::from(from == 0)
             + usize::from(to == 0)
             + usize::from(from == 6)
             + usize::from(to == 6);
-        ((self.0 >> shift) & mask == 0).then(|| 2 * delta as usize - edge_cases)
+        (start..start + delta)
+            .all(|place| self.places(place) == 0)
+            .then(|| 2 * delta - edge_cases)
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub struct DoorLayer(u16);
+crate::packed! {
+    /// The amphipod in each of the 4 rooms at one depth, or 0.
+    pub struct DoorLayer(u16) {
+        rooms: [u8; 4] = 4,
+    }
+}
 
 impl DoorLayer {
     fn from_chars(chars: [char; 4]) -> Self {
-        let from_char = |c| c as u8 - b'A' + 1;
-        Self(
-            chars
-                .iter()
-                .rev()
-                .map(|&occ| from_char(occ) as u16)
-                .reduce(|layer, occ| layer << 4 | occ)
-                .unwrap(),
-        )
-    }
-
-    fn set(&mut self, index: u8, occ: u8) {
-        self.0 &= !(0b1111 << (4 * index));
-        self.0 |= (occ as u16) << (4 * index);
+        let mut layer = Self::default();
+        for (index, c) in chars.iter().enumerate() {
+            layer.set_rooms(index, *c as u8 - b'A' + 1);
+        }
+        layer
     }
 
     fn get(self, index: u8) -> Option<u8> {
-        let occ = ((self.0 >> (4 * index)) & 0b1111) as u8;
-        (occ != 0).then(|| occ)
+        let occ = self.rooms(index as usize);
+        (occ != 0).then_some(occ)
     }
 }
 
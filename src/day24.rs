@@ -1,4 +1,8 @@
-use crate::day::Day;
+use crate::{
+    day::Day,
+    util::{self, Cursor},
+};
+use std::convert::TryFrom;
 
 #[derive(Debug, Clone, Copy)]
 pub enum Constraint {
@@ -19,6 +23,11 @@ impl Constraint {
     }
 }
 
+fn model_number(model: [i32; 14]) -> String {
+    let digits = model.iter().map(|&digit| digit as u64);
+    util::from_digits(digits, 10).unwrap().to_string()
+}
+
 pub struct Day24;
 
 impl<'a> Day<'a> for Day24 {
@@ -28,18 +37,38 @@ impl<'a> Day<'a> for Day24 {
     const DAY: usize = 24;
 
     fn parse(input: &'a str) -> Self::Input {
-        let lines = input.trim().lines().map(str::trim).collect::<Vec<_>>();
+        // every block is the same but for whether it divides z by 26 and the
+        // numbers it adds to x and y
+        let block = |cursor: &mut Cursor<'a>| {
+            cursor.literal("inp w\nmul x 0\nadd x z\nmod x 26\ndiv z ")?;
+            let start = *cursor;
+            let pops = match cursor.int()? {
+                1 => false,
+                26 => true,
+                _ => return Err(start.error("1 or 26")),
+            };
+            cursor.literal("\nadd x ")?;
+            let add_x = cursor.int::<i32>()?;
+            cursor.literal(
+                "\neql x w\neql x 0\nmul y 0\nadd y 25\nmul y x\nadd y 1\nmul z y\n\
+                mul y 0\nadd y w\nadd y ",
+            )?;
+            let add_y = cursor.int::<i32>()?;
+            cursor.literal("\nmul y x\nadd z y")?;
+            Ok((pops, add_x, add_y))
+        };
+        let blocks = util::parse(input, |cursor| {
+            let start = *cursor;
+            let blocks = cursor.lines(block)?;
+            <[_; 14]>::try_from(blocks).map_err(|_| start.error("14 blocks"))
+        })
+        .unwrap();
         let mut z = Vec::<(usize, i32)>::new();
         let mut constraints = [Constraint::Free; 14];
-        for (i, block) in lines[1..].split(|&line| line == lines[0]).enumerate() {
-            let mut divs_and_adds = block
-                .iter()
-                .filter(|line| line.starts_with("div") || line.starts_with("add"));
-            let div_z = divs_and_adds.nth(1).unwrap();
-            if div_z == &"div z 26" {
+        for (i, &(pops, add_x, add_y)) in blocks.iter().enumerate() {
+            if pops {
                 if let Some((j, mut add)) = z.pop() {
-                    let add_x = divs_and_adds.next().unwrap();
-                    add += add_x[6..].parse::<i32>().unwrap();
+                    add += add_x;
                     constraints[i] = Constraint::Equal(j, add);
                     constraints[j] = if add < 0 {
                         Constraint::Greater(1 - add)
@@ -48,8 +77,7 @@ impl<'a> Day<'a> for Day24 {
                     };
                 }
             } else {
-                let add_y = divs_and_adds.nth(4).unwrap();
-                z.push((i, add_y[6..].parse().unwrap()));
+                z.push((i, add_y));
             }
         }
         constraints
@@ -60,7 +88,7 @@ impl<'a> Day<'a> for Day24 {
         for (i, c) in constraints.iter().enumerate() {
             c.apply(i, &mut model);
         }
-        (constraints, model.iter().map(i32::to_string).collect())
+        (constraints, model_number(model))
     }
 
     fn solve_part2(constraints: Self::ProcessedInput) -> String {
@@ -68,13 +96,14 @@ impl<'a> Day<'a> for Day24 {
         for (i, c) in constraints.iter().enumerate() {
             c.apply(i, &mut model);
         }
-        model.iter().map(i32::to_string).collect()
+        model_number(model)
     }
 }
 
 #[cfg(test)]
 mod test_day24 {
     use super::*;
+    use hashbrown::HashSet;
     use indoc::indoc;
 
     const INPUT: &str = indoc! {"
@@ -340,6 +369,179 @@ mod test_day24 {
         let part2 = Day24::solve_part2(input);
         assert_eq!(part2, "11419161313147");
     }
+
+    #[derive(Clone, Copy)]
+    enum Operand {
+        Register(usize),
+        Number(i64),
+    }
+
+    /// An ALU instruction other than `inp`: what it does, and the registers
+    /// it does it to.
+    type Instruction = (fn(i64, i64) -> i64, usize, Operand);
+
+    /// Run one block of the ALU program, the instructions after an `inp`,
+    /// on the digit `w` with `z` left over from the blocks before.
+    fn run(block: &[Instruction], w: i64, z: i64) -> i64 {
+        let mut registers = [w, 0, 0, z];
+        for &(op, a, b) in block {
+            let b = match b {
+                Operand::Register(b) => registers[b],
+                Operand::Number(b) => b,
+            };
+            registers[a] = op(registers[a], b);
+        }
+        registers[3]
+    }
+
+    /// Try every digit in `digits` order at each block, giving up on any `z`
+    /// with more base-26 digits than the blocks left can take off it, and
+    /// remembering the `(block, z)`s that lead nowhere.
+    fn search(
+        blocks: &[Vec<Instruction>],
+        pops: &[i32],
+        digits: &[i64],
+        (i, z): (usize, i64),
+        dead_ends: &mut HashSet<(usize, i64)>,
+    ) -> Option<Vec<i64>> {
+        if i == blocks.len() {
+            return (z == 0).then(Vec::new);
+        }
+        if pops[i] < 0 || z >= 26_i64.pow(pops[i] as u32) || dead_ends.contains(&(i, z)) {
+            return None;
+        }
+        for &w in digits {
+            let next = (i + 1, run(&blocks[i], w, z));
+            if let Some(mut model) = search(blocks, pops, digits, next, dead_ends) {
+                model.insert(0, w);
+                return Some(model);
+            }
+        }
+        dead_ends.insert((i, z));
+        None
+    }
+
+    fn reference(input: &str) -> [String; 2] {
+        let register = |name: &str| (name.as_bytes()[0] - b'w') as usize;
+        let (mut blocks, mut divides) = (Vec::<Vec<Instruction>>::new(), Vec::new());
+        let mut never_matches = Vec::new();
+        for line in input.trim().lines() {
+            let words = line.split_whitespace().collect::<Vec<_>>();
+            if words[0] == "inp" {
+                blocks.push(Vec::new());
+                divides.push(false);
+                never_matches.push(false);
+                continue;
+            }
+            if words == ["div", "z", "26"] {
+                *divides.last_mut().unwrap() = true;
+            }
+            if words[..2] == ["add", "x"] && words[2].parse().is_ok_and(|n: i64| n > 9) {
+                *never_matches.last_mut().unwrap() = true;
+            }
+            let op: fn(i64, i64) -> i64 = match words[0] {
+                "add" => |a, b| a + b,
+                "mul" => |a, b| a * b,
+                "div" => |a, b| a / b,
+                "mod" => |a, b| a % b,
+                "eql" => |a, b| (a == b) as i64,
+                op => panic!("unknown instruction {}", op),
+            };
+            let b = words[2]
+                .parse()
+                .map_or_else(|_| Operand::Register(register(words[2])), Operand::Number);
+            blocks
+                .last_mut()
+                .unwrap()
+                .push((op, register(words[1]), b));
+        }
+        // how many base-26 digits the blocks from each one on can take off
+        // z: one for each that divides it by 26 and can match its digit,
+        // less one for each that can't match and doesn't divide, and so
+        // multiplies z by 26
+        let mut pops = vec![0; blocks.len()];
+        for i in (0..blocks.len()).rev() {
+            let pop = match (divides[i], never_matches[i]) {
+                (true, false) => 1,
+                (false, true) => -1,
+                _ => 0,
+            };
+            pops[i] = pops.get(i + 1).copied().unwrap_or(0) + pop;
+        }
+        // a dead end is one whatever digits follow, so both searches share them
+        let mut dead_ends = HashSet::new();
+        let mut model = |digits: &[i64]| {
+            let start = (0, 0);
+            let model = search(&blocks, &pops, digits, start, &mut dead_ends).unwrap();
+            model.iter().map(|d| d.to_string()).collect::<String>()
+        };
+        [
+            model(&[9, 8, 7, 6, 5, 4, 3, 2, 1]),
+            model(&[1, 2, 3, 4, 5, 6, 7, 8, 9]),
+        ]
+    }
+
+    /// A program in the puzzle's template where each block either pushes
+    /// its digit plus `n` onto z in base 26, or pops one off and checks it
+    /// against its own digit less `n`.
+    fn program(blocks: &[(bool, i64); 14]) -> String {
+        let mut stack = Vec::new();
+        let mut program = String::new();
+        for &(push, n) in blocks {
+            let (div_z, add_x, add_y) = if push {
+                stack.push(n);
+                (1, 12, n)
+            } else {
+                (26, n - stack.pop().unwrap(), 3)
+            };
+            program += &format!(
+                "inp w\nmul x 0\nadd x z\nmod x 26\ndiv z {}\nadd x {}\neql x w\neql x 0\n\
+                mul y 0\nadd y 25\nmul y x\nadd y 1\nmul z y\nmul y 0\nadd y w\nadd y {}\n\
+                mul y x\nadd z y\n",
+                div_z, add_x, add_y
+            );
+        }
+        program
+    }
+
+    #[test]
+    fn test_day24_against_reference() {
+        let generate = |random: &mut util::Random| {
+            let mut blocks = [(true, 0); 14];
+            let (mut pushes, mut pops) = (0, 0);
+            for block in &mut blocks {
+                // push while there's room left to pop everything
+                let depth = pushes - pops;
+                let push = pushes < 7 && depth < 4 && (depth == 0 || random.flip());
+                *block = if push {
+                    pushes += 1;
+                    (true, random.range(0..=16))
+                } else {
+                    pops += 1;
+                    (false, random.range(-8..=8))
+                };
+            }
+            blocks
+        };
+        util::check(100, 24, generate, |blocks| {
+            // every pop needs a push before it, adding at most 16 so as not
+            // to carry into the next place, and the digits it checks must
+            // be able to differ by its `n`; the reference tries every digit
+            // for each push still on the stack, so it can't be too deep
+            let mut depth = 0;
+            for &(push, n) in blocks {
+                depth += if push { 1 } else { -1 };
+                let fits = if push { (0..=16).contains(&n) } else { n.abs() <= 8 };
+                if !(0..=4).contains(&depth) || !fits {
+                    return Ok(());
+                }
+            }
+            if depth != 0 {
+                return Ok(());
+            }
+            util::agree(&program(blocks), |input| Day24::solve(input), reference)
+        });
+    }
 }
 
 bench_day!(24);
@@ -1,4 +1,8 @@
-use crate::day::Day;
+use crate::{
+    day::Day,
+    util::{self, Automaton, Boundary, Frame, Grid, Renderer, Rgb, MOORE},
+};
+use std::io;
 
 // TODO: 4 tiles can be packed into one u8
 
@@ -6,88 +10,80 @@ use crate::day::Day;
 pub enum TileState {
     East,
     South,
-    Moved,
     Empty,
 }
 
-fn step(width: usize, height: usize, cucumbers: &mut [TileState]) -> bool {
-    let mut stepped = false;
-    for y in 0..height {
-        let mut x = 0;
-        while x < width {
-            if cucumbers[x + y * width] == TileState::East {
-                let step_x = (x + 1) % width;
-                if cucumbers[step_x + y * width] == TileState::Empty {
-                    cucumbers[step_x + y * width] = TileState::East;
-                    cucumbers[x + y * width] = TileState::Moved;
-                    stepped = true;
-                    x += 1;
-                }
-            }
-            x += 1;
-        }
-    }
-    cucumbers
-        .iter_mut()
-        .filter(|s| **s == TileState::Moved)
-        .for_each(|s| *s = TileState::Empty);
-    for x in 0..width {
-        let mut y = 0;
-        while y < height {
-            if cucumbers[x + y * width] == TileState::South {
-                let step_y = (y + 1) % height;
-                if cucumbers[x + step_y * width] == TileState::Empty {
-                    cucumbers[x + step_y * width] = TileState::South;
-                    cucumbers[x + y * width] = TileState::Moved;
-                    stepped = true;
-                    y += 1;
-                }
-            }
-            y += 1;
+/// A whole step at once: the east-facing herd moves along each of the three
+/// rows around a tile, then the south-facing herd moves down its column.
+fn step(tile: &TileState, neighbours: &[&TileState]) -> TileState {
+    let east = |left, tile, right| match (left, tile, right) {
+        (TileState::East, TileState::Empty, _) => TileState::East,
+        (_, TileState::East, TileState::Empty) => TileState::Empty,
+        _ => tile,
+    };
+    let south = |up, tile, down| match (up, tile, down) {
+        (TileState::South, TileState::Empty, _) => TileState::South,
+        (_, TileState::South, TileState::Empty) => TileState::Empty,
+        _ => tile,
+    };
+    match *neighbours {
+        [&nw, &n, &ne, &w, &e, &sw, &s, &se] => {
+            south(east(nw, n, ne), east(w, *tile, e), east(sw, s, se))
         }
+        _ => unreachable!("a torus has all 8 neighbours"),
     }
-    cucumbers
-        .iter_mut()
-        .filter(|s| **s == TileState::Moved)
-        .for_each(|s| *s = TileState::Empty);
-    stepped
 }
 
 pub struct Day25;
 
 impl<'a> Day<'a> for Day25 {
-    type Input = (usize, usize, Vec<TileState>);
+    type Input = Grid<TileState>;
     type ProcessedInput = ();
 
     const DAY: usize = 25;
 
     fn parse(input: &'a str) -> Self::Input {
-        let width = input.find('\n').unwrap();
-        let cucumbers = input
-            .lines()
-            .flat_map(|line| {
-                line.chars().map(|c| match c {
-                    '>' => TileState::East,
-                    'v' => TileState::South,
-                    _ => TileState::Empty,
-                })
+        util::parse(input, |cursor| {
+            cursor.grid(|c| match c {
+                '>' => Some(TileState::East),
+                'v' => Some(TileState::South),
+                '.' => Some(TileState::Empty),
+                _ => None,
             })
-            .collect::<Vec<_>>();
-        let height = cucumbers.len() / width;
-        (width, height, cucumbers)
+        })
+        .unwrap()
     }
 
-    fn solve_part1((width, height, mut cucumbers): Self::Input) -> (Self::ProcessedInput, String) {
-        let mut steps = 1;
-        while step(width, height, &mut cucumbers) {
-            steps += 1;
-        }
-        ((), steps.to_string())
+    fn solve_part1(cucumbers: Self::Input) -> (Self::ProcessedInput, String) {
+        let mut cucumbers = Automaton::new(cucumbers, &MOORE, Boundary::Wrapping, step);
+        ((), cucumbers.run_until_stable().to_string())
     }
 
     fn solve_part2(_: Self::ProcessedInput) -> String {
         "Merry Christmas!".to_string()
     }
+
+    fn render(input: &'a str, renderer: &mut Renderer) -> io::Result<()> {
+        // the herds step by step until they stop, or until it's clear they
+        // go round and round
+        const MAX_STEPS: usize = 1000;
+        let paint = |tile: &TileState| -> Rgb {
+            match tile {
+                TileState::East => [255, 128, 0],
+                TileState::South => [0, 192, 255],
+                TileState::Empty => [0; 3],
+            }
+        };
+        let mut cucumbers = Automaton::new(Self::parse(input), &MOORE, Boundary::Wrapping, step);
+        renderer.draw(&Frame::color(cucumbers.grid(), paint))?;
+        for _ in 0..MAX_STEPS {
+            if !cucumbers.step() {
+                break;
+            }
+            renderer.draw(&Frame::color(cucumbers.grid(), paint))?;
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -113,6 +109,21 @@ mod test_day25 {
         let (_, part1) = Day25::solve_part1(input);
         assert_eq!(part1, "58");
     }
+
+    #[test]
+    fn test_day25_render() {
+        // the start, then every step that moved anything
+        let mut renderer = Renderer::memory();
+        Day25::render(EXAMPLE, &mut renderer).unwrap();
+        assert_eq!(renderer.frames(), 58);
+        let frames = renderer.drawn();
+        assert!(frames.iter().all(|f| (f.width(), f.height()) == (10, 9)));
+
+        // a lone cucumber on a loop never stops
+        let mut renderer = Renderer::memory();
+        Day25::render(">.\n", &mut renderer).unwrap();
+        assert_eq!(renderer.frames(), 1 + 1000);
+    }
 }
 
 bench_day!(25);
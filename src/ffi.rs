@@ -0,0 +1,191 @@
+use crate::{
+    corpus::{self, Outcome},
+    solvers,
+};
+use std::{os::raw::c_char, slice, str};
+
+/// The size of the answer buffers in `AocSolution`, including the nul.
+pub const AOC_ANSWER_LEN: usize = 64;
+
+pub const AOC_OK: i32 = 0;
+pub const AOC_NO_SUCH_DAY: i32 = 1;
+pub const AOC_NULL_POINTER: i32 = 2;
+pub const AOC_INVALID_UTF8: i32 = 3;
+pub const AOC_SOLVER_PANICKED: i32 = 4;
+pub const AOC_ANSWER_TOO_LONG: i32 = 5;
+
+const STATUSES: [(&str, i32, &str); 6] = [
+    ("AOC_OK", AOC_OK, "ok\0"),
+    (
+        "AOC_NO_SUCH_DAY",
+        AOC_NO_SUCH_DAY,
+        "no solver for that day\0",
+    ),
+    (
+        "AOC_NULL_POINTER",
+        AOC_NULL_POINTER,
+        "null pointer argument\0",
+    ),
+    (
+        "AOC_INVALID_UTF8",
+        AOC_INVALID_UTF8,
+        "input is not valid UTF-8\0",
+    ),
+    (
+        "AOC_SOLVER_PANICKED",
+        AOC_SOLVER_PANICKED,
+        "solver panicked (malformed input?)\0",
+    ),
+    (
+        "AOC_ANSWER_TOO_LONG",
+        AOC_ANSWER_TOO_LONG,
+        "answer doesn't fit in the output buffer\0",
+    ),
+];
+
+#[repr(C)]
+pub struct AocSolution {
+    pub part1: [c_char; AOC_ANSWER_LEN],
+    pub part2: [c_char; AOC_ANSWER_LEN],
+    pub parse_ms: f64,
+    pub part1_ms: f64,
+    pub part2_ms: f64,
+}
+
+fn write_answer(answer: &str, buffer: &mut [c_char; AOC_ANSWER_LEN]) -> Result<(), i32> {
+    if answer.len() >= AOC_ANSWER_LEN {
+        return Err(AOC_ANSWER_TOO_LONG);
+    }
+    for (c, b) in buffer.iter_mut().zip(answer.bytes()) {
+        *c = b as c_char;
+    }
+    buffer[answer.len()] = 0;
+    Ok(())
+}
+
+fn solve(day: u32, input: &[u8], out: &mut AocSolution) -> Result<(), i32> {
+    let solver = solvers()
+        .into_iter()
+        .find(|&(n, _)| n as u32 == day)
+        .ok_or(AOC_NO_SUCH_DAY)?
+        .1;
+    let input = str::from_utf8(input).map_err(|_| AOC_INVALID_UTF8)?;
+    match corpus::evaluate(solver, input, None) {
+        Outcome::Solved(solution, _) => {
+            write_answer(&solution.part1, &mut out.part1)?;
+            write_answer(&solution.part2, &mut out.part2)?;
+            out.parse_ms = 1000.0 * solution.parse_time.as_secs_f64();
+            out.part1_ms = 1000.0 * solution.part1_time.as_secs_f64();
+            out.part2_ms = 1000.0 * solution.part2_time.as_secs_f64();
+            Ok(())
+        }
+        Outcome::Panicked(_) => Err(AOC_SOLVER_PANICKED),
+    }
+}
+
+/// Solve both parts of a day, writing the nul-terminated answers and the
+/// timings into `out`. Returns `AOC_OK` or one of the other status codes.
+///
+/// # Safety
+///
+/// `input_ptr` must point to `input_len` readable bytes (it may be null if
+/// `input_len` is 0) and `out` must point to a writable `AocSolution`.
+#[no_mangle]
+pub unsafe extern "C" fn aoc_solve(
+    day: u32,
+    input_ptr: *const u8,
+    input_len: usize,
+    out: *mut AocSolution,
+) -> i32 {
+    let input = match (input_ptr.is_null(), input_len) {
+        (true, 0) => &[],
+        (true, _) => return AOC_NULL_POINTER,
+        (false, _) => slice::from_raw_parts(input_ptr, input_len),
+    };
+    match out.as_mut() {
+        Some(out) => solve(day, input, out).err().unwrap_or(AOC_OK),
+        None => AOC_NULL_POINTER,
+    }
+}
+
+/// A static nul-terminated description of a status code.
+#[no_mangle]
+pub extern "C" fn aoc_status_message(status: i32) -> *const c_char {
+    STATUSES
+        .iter()
+        .find(|(_, code, _)| *code == status)
+        .map_or("unknown status\0", |(_, _, message)| message)
+        .as_ptr() as *const c_char
+}
+
+/// The C header declaring this API, as checked in at `ffi/aoc2021.h`.
+pub fn header() -> String {
+    let mut header = String::from(
+        "/* Generated by `cargo run --release header`; don't edit by hand. */\n\
+        \n\
+        #ifndef AOC2021_H\n\
+        #define AOC2021_H\n\
+        \n\
+        #include <stddef.h>\n\
+        #include <stdint.h>\n\
+        \n",
+    );
+    header += &format!("#define AOC_ANSWER_LEN {}\n\n", AOC_ANSWER_LEN);
+    for (name, code, _) in STATUSES.iter() {
+        header += &format!("#define {} {}\n", name, code);
+    }
+    header += "\n\
+        typedef struct {\n    \
+            char part1[AOC_ANSWER_LEN];\n    \
+            char part2[AOC_ANSWER_LEN];\n    \
+            double parse_ms;\n    \
+            double part1_ms;\n    \
+            double part2_ms;\n\
+        } AocSolution;\n\
+        \n\
+        int32_t aoc_solve(uint32_t day, const uint8_t *input_ptr, size_t input_len,\n                  \
+            AocSolution *out);\n\
+        \n\
+        const char *aoc_status_message(int32_t status);\n\
+        \n\
+        #endif\n";
+    header
+}
+
+#[cfg(test)]
+mod test_ffi {
+    use super::*;
+    use std::{ffi::CStr, fs, mem::MaybeUninit, ptr};
+
+    fn solve(day: u32, input: &str) -> (i32, AocSolution) {
+        let mut out = MaybeUninit::<AocSolution>::zeroed();
+        let status = unsafe { aoc_solve(day, input.as_ptr(), input.len(), out.as_mut_ptr()) };
+        (status, unsafe { out.assume_init() })
+    }
+
+    #[test]
+    fn test_ffi_solve() {
+        let (status, solution) = solve(1, "199\n200\n208\n210\n200\n207\n240\n269\n260\n263");
+        assert_eq!(status, AOC_OK);
+        let part1 = unsafe { CStr::from_ptr(solution.part1.as_ptr()) };
+        let part2 = unsafe { CStr::from_ptr(solution.part2.as_ptr()) };
+        assert_eq!(part1.to_str(), Ok("7"));
+        assert_eq!(part2.to_str(), Ok("5"));
+    }
+
+    #[test]
+    fn test_ffi_errors() {
+        assert_eq!(solve(26, "").0, AOC_NO_SUCH_DAY);
+        assert_eq!(solve(17, "garbage").0, AOC_SOLVER_PANICKED);
+        let null_out = unsafe { aoc_solve(1, ptr::null(), 0, ptr::null_mut()) };
+        assert_eq!(null_out, AOC_NULL_POINTER);
+        let message = unsafe { CStr::from_ptr(aoc_status_message(AOC_NO_SUCH_DAY)) };
+        assert_eq!(message.to_str(), Ok("no solver for that day"));
+    }
+
+    #[test]
+    fn test_ffi_header_up_to_date() {
+        let checked_in = fs::read_to_string("ffi/aoc2021.h").unwrap();
+        assert_eq!(checked_in, header());
+    }
+}
@@ -0,0 +1,93 @@
+#![cfg_attr(feature = "bench", feature(test))]
+
+#[cfg(feature = "bench")]
+extern crate test;
+
+pub mod corpus;
+pub mod day;
+pub mod ffi;
+pub mod serve;
+pub mod util;
+pub mod watch;
+
+use day::{Day, Render, Solver};
+use std::error::Error;
+use util::Renderer;
+
+#[macro_export]
+macro_rules! bench_day {
+    ($day:literal) => {
+        paste::paste! {
+            #[cfg(all(feature = "bench", test, not(debug_assertions)))]
+            mod [<bench_day $day>] {
+                use $crate::day::Day;
+                use super::*;
+                use test::Bencher;
+
+                #[bench]
+                fn [<bench_day $day _overall>] (b: &mut Bencher) {
+                    let input = [<Day $day>]::get_input().unwrap();
+                    b.iter(|| {
+                        let input = [<Day $day>]::parse(&input);
+                        let (input, part1) = [<Day $day>]::solve_part1(input);
+                        let part2 = [<Day $day>]::solve_part2(input);
+                        (part1, part2)
+                    })
+                }
+            }
+        }
+    };
+}
+
+macro_rules! import_days {
+    ($day:literal) => {
+        paste::paste! {
+            mod [<day $day>];
+            pub use [<day $day>]::[<Day $day>];
+        }
+    };
+    ($day:literal, $($days:literal),+) => {
+        import_days!($day);
+        import_days!($($days),+);
+    }
+}
+
+macro_rules! solver {
+    ($day:literal) => {{
+        paste::paste! {
+            ([<Day $day>]::DAY, |input: &str| [<Day $day>]::solve(input))
+        }
+    }};
+}
+
+macro_rules! renderer {
+    ($day:literal) => {{
+        paste::paste! {
+            ([<Day $day>]::DAY, |renderer: &mut Renderer| -> Result<(), Box<dyn Error>> {
+                let input = [<Day $day>]::get_input()?;
+                Ok([<Day $day>]::render(&input, renderer)?)
+            })
+        }
+    }};
+}
+
+macro_rules! declare_days {
+    ($($days:literal),+) => {
+        import_days!($($days),+);
+
+        /// The day number and solver of each day.
+        pub fn solvers() -> Vec<(usize, Solver)> {
+            vec![$(solver!($days)),+]
+        }
+
+        /// The day number and renderer of each day.
+        pub fn renderers() -> Vec<(usize, Render)> {
+            vec![$(renderer!($days)),+]
+        }
+    };
+}
+
+declare_days!(
+    01, 02, 03, 04, 05, 06, 07, 08, 09, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24,
+    25
+);
@@ -1,46 +1,10 @@
-#![feature(test)]
-#![feature(int_abs_diff)]
-#![feature(type_alias_impl_trait)]
-
-extern crate test;
-
-mod day;
-mod util;
-
-use day::Day;
-use std::env;
-
-#[macro_export]
-macro_rules! bench_day {
-    ($day:literal) => {
-        paste::paste! {
-            #[cfg(not(debug_assertions))]
-            #[cfg(test)]
-            mod [<bench_day $day>] {
-                use crate::day::Day;
-                use super::*;
-                use test::Bencher;
-
-                #[bench]
-                fn [<bench_day $day _overall>] (b: &mut Bencher) {
-                    let input = [<Day $day>]::get_input().unwrap();
-                    b.iter(|| {
-                        let input = [<Day $day>]::parse(&input);
-                        let (input, part1) = [<Day $day>]::solve_part1(input);
-                        let part2 = [<Day $day>]::solve_part2(input);
-                        (part1, part2)
-                    })
-                }
-            }
-        }
-    };
-}
+use aoc2021::{corpus, day::Day, ffi, renderers, serve, solvers, util::Renderer, watch};
+use std::{env, path::Path};
 
 macro_rules! import_days {
     ($day:literal) => {
         paste::paste! {
-            mod [<day $day>];
-            use [<day $day>]::[<Day $day>];
+            use aoc2021::[<Day $day>];
         }
     };
     ($day:literal, $($days:literal),+) => {
@@ -81,10 +45,56 @@ macro_rules! match_days {
 macro_rules! declare_main {
     ($last_day:literal => $($days:literal),+) => {
         import_days!($($days),+);
+
         fn main() {
             #[allow(clippy::zero_prefixed_literal)]
             match env::args().nth(1).as_deref() {
                 None => solve!($last_day),
+                Some("corpus") => {
+                    let dir = env::args().nth(2).unwrap_or_else(|| "inputs".to_string());
+                    if let Err(err) = corpus::run(Path::new(&dir), &solvers()) {
+                        eprintln!("Couldn't read corpus directory {} ({})", dir, err);
+                    }
+                }
+                Some("header") => print!("{}", ffi::header()),
+                Some("dot") => match Day12::get_input() {
+                    // the caves as given with --full, else as the solver sees them
+                    Ok(input) => {
+                        let contracted = env::args().nth(2).as_deref() != Some("--full");
+                        print!("{}", Day12::to_dot(&input, contracted))
+                    }
+                    Err(err) => eprintln!("{}", err),
+                },
+                Some("--render") => {
+                    let day = env::args().nth(2).and_then(|day| day.parse().ok());
+                    let mut renderer = match env::args().nth(3) {
+                        Some(dir) => Renderer::images(dir),
+                        None => Renderer::terminal(),
+                    };
+                    match renderers().into_iter().find(|&(n, _)| Some(n) == day) {
+                        Some((day, render)) => match render(&mut renderer) {
+                            Err(err) => eprintln!("Couldn't render day{:02} ({})", day, err),
+                            Ok(()) if renderer.frames() == 0 => {
+                                eprintln!("day{:02} has nothing to render", day)
+                            }
+                            Ok(()) => {}
+                        },
+                        None => eprintln!("Expected day number as argument to render"),
+                    }
+                }
+                Some("serve") => {
+                    let address = env::args().nth(2).unwrap_or_else(|| "127.0.0.1:8021".to_string());
+                    if let Err(err) = serve::run(&address, &solvers()) {
+                        eprintln!("Couldn't serve on {} ({})", address, err);
+                    }
+                }
+                Some("watch") => {
+                    let day = env::args().nth(2).and_then(|day| day.parse().ok());
+                    match solvers().into_iter().find(|&(n, _)| Some(n) == day) {
+                        Some((day, solver)) => watch::run(day, solver),
+                        None => eprintln!("Expected day number as argument to watch"),
+                    }
+                }
                 Some(day) => match_days!(day, $($days),+),
             }
         }
@@ -0,0 +1,233 @@
+use crate::{
+    corpus::{self, Outcome},
+    day::Solver,
+};
+use std::{
+    fmt::Write as _,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    time::{Duration, Instant},
+};
+
+/// The largest body accepted, well over the size of any puzzle input.
+const MAX_BODY: usize = 4 << 20;
+
+/// The most accepted for the request line and headers together, which also
+/// bounds how many headers there can be.
+const MAX_HEAD: usize = 8 << 10;
+
+/// How long a client gets to send its whole request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+struct Request {
+    method: String,
+    path: String,
+    body: Vec<u8>,
+}
+
+struct Response {
+    status: u16,
+    body: String,
+}
+
+impl Response {
+    fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            body: format!("{{\"error\":{}}}", json_string(message)),
+        }
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            413 => "Payload Too Large",
+            422 => "Unprocessable Entity",
+            431 => "Request Header Fields Too Large",
+            _ => "",
+        }
+    }
+}
+
+fn json_string(string: &str) -> String {
+    let mut json = String::with_capacity(string.len() + 2);
+    json.push('"');
+    for c in string.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => write!(json, "\\u{:04x}", c as u32).unwrap(),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+/// A stream that times out once `deadline` passes, however slowly the client
+/// trickles its bytes in.
+struct Deadline<'a> {
+    stream: &'a TcpStream,
+    deadline: Instant,
+}
+
+impl Read for Deadline<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let left = self.deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return Err(io::ErrorKind::TimedOut.into());
+        }
+        self.stream.set_read_timeout(Some(left))?;
+        let mut stream = self.stream;
+        stream.read(buf)
+    }
+}
+
+/// Read a line of the request head, or `None` if the head is too long.
+fn read_head_line<R: BufRead>(reader: &mut io::Take<R>) -> io::Result<Option<String>> {
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    match line.ends_with('\n') {
+        true => Ok(Some(line)),
+        false if reader.limit() == 0 => Ok(None),
+        false => Err(io::ErrorKind::UnexpectedEof.into()),
+    }
+}
+
+/// Read a request, or the error response to send if it isn't one that can
+/// be handled.
+fn read_request(stream: &TcpStream) -> io::Result<Result<Request, Response>> {
+    let deadline = Instant::now() + REQUEST_TIMEOUT;
+    let mut reader = BufReader::new(Deadline { stream, deadline }).take(MAX_HEAD as u64);
+    let too_large = || {
+        let message = format!("request head must be at most {} bytes", MAX_HEAD);
+        Ok(Err(Response::error(431, &message)))
+    };
+    let request_line = match read_head_line(&mut reader)? {
+        Some(line) => line,
+        None => return too_large(),
+    };
+    let mut words = request_line.split_whitespace();
+    let (method, path) = match (words.next(), words.next()) {
+        (Some(method), Some(path)) => (method.to_string(), path.to_string()),
+        _ => return Ok(Err(Response::error(400, "malformed request"))),
+    };
+    let mut content_length = 0;
+    loop {
+        let header = match read_head_line(&mut reader)? {
+            Some(line) => line,
+            None => return too_large(),
+        };
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                match value.trim().parse() {
+                    Ok(length) => content_length = length,
+                    Err(_) => return Ok(Err(Response::error(400, "malformed request"))),
+                }
+            }
+        }
+    }
+    if content_length > MAX_BODY {
+        let message = format!("input must be at most {} bytes", MAX_BODY);
+        return Ok(Err(Response::error(413, &message)));
+    }
+    let mut body = Vec::new();
+    reader.set_limit(content_length as u64);
+    reader.read_to_end(&mut body)?;
+    if body.len() < content_length {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(Ok(Request { method, path, body }))
+}
+
+fn solve(day: usize, solver: Solver, input: &str) -> Response {
+    match corpus::evaluate(solver, input, None) {
+        Outcome::Solved(solution, _) => Response {
+            status: 200,
+            body: format!(
+                "{{\"day\":{},\"part1\":{},\"part2\":{},\"timings_ms\":\
+                {{\"parse\":{},\"part1\":{},\"part2\":{}}}}}",
+                day,
+                json_string(&solution.part1),
+                json_string(&solution.part2),
+                1000.0 * solution.parse_time.as_secs_f64(),
+                1000.0 * solution.part1_time.as_secs_f64(),
+                1000.0 * solution.part2_time.as_secs_f64(),
+            ),
+        },
+        Outcome::Panicked(message) => {
+            Response::error(422, &format!("couldn't solve input: {}", message))
+        }
+    }
+}
+
+fn route(request: &Request, solvers: &[(usize, Solver)]) -> Response {
+    let segments = request
+        .path
+        .trim_matches('/')
+        .split('/')
+        .collect::<Vec<_>>();
+    match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["days"]) => Response {
+            status: 200,
+            body: format!(
+                "[{}]",
+                solvers
+                    .iter()
+                    .map(|(day, _)| day.to_string())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ),
+        },
+        ("POST", ["day", day]) => match solvers.iter().find(|(n, _)| Ok(*n) == day.parse()) {
+            None => Response::error(404, &format!("no solver for day {}", day)),
+            Some(&(day, solver)) => match std::str::from_utf8(&request.body) {
+                Ok(input) => solve(day, solver, input),
+                Err(_) => Response::error(400, "input must be valid UTF-8"),
+            },
+        },
+        (_, ["days"]) | (_, ["day", _]) => Response::error(405, "method not allowed"),
+        _ => Response::error(404, "not found"),
+    }
+}
+
+fn handle(mut stream: TcpStream, solvers: &[(usize, Solver)]) -> io::Result<()> {
+    let response = match read_request(&stream)? {
+        Ok(request) => route(&request, solvers),
+        Err(response) => response,
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\n\
+        Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        response.reason(),
+        response.body.len(),
+        response.body
+    )?;
+    stream.flush()
+}
+
+/// Serve the solvers over HTTP: `POST /day/<n>` with the puzzle input as the
+/// body responds with the answers and timings as JSON, and `GET /days` lists
+/// the available days. Requests are handled one at a time.
+pub fn run(address: &str, solvers: &[(usize, Solver)]) -> io::Result<()> {
+    let listener = TcpListener::bind(address)?;
+    println!("serving on http://{}", listener.local_addr()?);
+    for stream in listener.incoming() {
+        if let Err(err) = stream.and_then(|stream| handle(stream, solvers)) {
+            eprintln!("Couldn't handle request ({})", err);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,281 @@
+use super::Grid;
+use hashbrown::HashMap;
+use std::hash::Hash;
+
+/// The 4 orthogonal neighbours, in reading order.
+pub const VON_NEUMANN: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+
+/// The 8 orthogonal and diagonal neighbours, in reading order.
+pub const MOORE: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+/// What lies beyond the edges of an automaton's grid.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Boundary<T> {
+    /// Nothing, so cells at the edges have fewer neighbours.
+    Fixed,
+    /// The other side of the grid, as on a torus.
+    Wrapping,
+    /// An infinite plane of cells all in the same state, which evolves along
+    /// with the grid. The grid grows each step to take in the cells that the
+    /// pattern can reach.
+    Background(T),
+}
+
+/// A grid of cells that all step at once, each to `rule(cell, neighbours)`.
+/// The neighbours are the cells at the offsets of the neighbourhood, in its
+/// order, leaving out any that are off a grid with a fixed boundary.
+pub struct Automaton<T, R> {
+    grid: Grid<T>,
+    neighbourhood: Vec<(isize, isize)>,
+    boundary: Boundary<T>,
+    rule: R,
+    generation: usize,
+}
+
+impl<T: Clone + PartialEq, R: Fn(&T, &[&T]) -> T> Automaton<T, R> {
+    pub fn new(
+        grid: Grid<T>,
+        neighbourhood: &[(isize, isize)],
+        boundary: Boundary<T>,
+        rule: R,
+    ) -> Self {
+        Self {
+            grid,
+            neighbourhood: neighbourhood.to_vec(),
+            boundary,
+            rule,
+            generation: 0,
+        }
+    }
+
+    pub fn grid(&self) -> &Grid<T> {
+        &self.grid
+    }
+
+    /// For changes made between steps, outside of the rule.
+    pub fn grid_mut(&mut self) -> &mut Grid<T> {
+        &mut self.grid
+    }
+
+    /// The state of every cell off the grid, if it has a background.
+    pub fn background(&self) -> Option<&T> {
+        match &self.boundary {
+            Boundary::Background(background) => Some(background),
+            _ => None,
+        }
+    }
+
+    /// How many steps have been taken.
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    fn cell(&self, x: isize, y: isize) -> Option<&T> {
+        let (width, height) = (self.grid.width() as isize, self.grid.height() as isize);
+        if (0..width).contains(&x) && (0..height).contains(&y) {
+            return Some(&self.grid[(x as usize, y as usize)]);
+        }
+        match &self.boundary {
+            Boundary::Fixed => None,
+            Boundary::Wrapping => {
+                let (x, y) = (x.rem_euclid(width), y.rem_euclid(height));
+                Some(&self.grid[(x as usize, y as usize)])
+            }
+            Boundary::Background(background) => Some(background),
+        }
+    }
+
+    /// How far the grid grows on each side in a step.
+    fn margin(&self) -> isize {
+        match self.boundary {
+            Boundary::Background(_) => self
+                .neighbourhood
+                .iter()
+                .map(|&(dx, dy)| dx.abs().max(dy.abs()))
+                .max()
+                .unwrap_or(0),
+            _ => 0,
+        }
+    }
+
+    /// The part of the grid that differs from the background, and where
+    /// that is relative to the grid's top left `origin`, which moves as it
+    /// grows. Without a background that's the whole grid.
+    fn pattern(&self, origin: (isize, isize)) -> (Grid<T>, (isize, isize)) {
+        let background = match &self.boundary {
+            Boundary::Background(background) => background,
+            _ => return (self.grid.clone(), (0, 0)),
+        };
+        let points = self.grid.points().filter(|&p| self.grid[p] != *background);
+        let (mut min, mut max) = ((usize::MAX, usize::MAX), (0, 0));
+        for (x, y) in points {
+            min = (min.0.min(x), min.1.min(y));
+            max = (max.0.max(x + 1), max.1.max(y + 1));
+        }
+        if min.0 > max.0 {
+            return (Grid::new(0, 0, Vec::new()), (0, 0));
+        }
+        let trimmed = Grid::from_fn(max.0 - min.0, max.1 - min.1, |(x, y)| {
+            self.grid[(x + min.0, y + min.1)].clone()
+        });
+        (
+            trimmed,
+            (origin.0 + min.0 as isize, origin.1 + min.1 as isize),
+        )
+    }
+
+    /// Advance a generation, returning whether any cell changed. A grid
+    /// with a background grows, so there only the cells that differ from
+    /// the background, and where they are, count.
+    pub fn step(&mut self) -> bool {
+        let margin = self.margin();
+        let before = self.background().map(|_| self.pattern((0, 0)));
+        let width = self.grid.width() + 2 * margin as usize;
+        let height = self.grid.height() + 2 * margin as usize;
+        let mut neighbours = Vec::with_capacity(self.neighbourhood.len());
+        let next = Grid::from_fn(width, height, |(x, y)| {
+            let (x, y) = (x as isize - margin, y as isize - margin);
+            neighbours.clear();
+            neighbours.extend(
+                self.neighbourhood
+                    .iter()
+                    .filter_map(|&(dx, dy)| self.cell(x + dx, y + dy)),
+            );
+            (self.rule)(self.cell(x, y).unwrap(), &neighbours)
+        });
+        let mut changed = next != self.grid;
+        self.grid = next;
+        if let Boundary::Background(background) = &self.boundary {
+            let next = (self.rule)(background, &vec![background; self.neighbourhood.len()]);
+            changed = next != *background;
+            self.boundary = Boundary::Background(next);
+            changed |= before != Some(self.pattern((-margin, -margin)));
+        }
+        self.generation += 1;
+        changed
+    }
+
+    pub fn run(&mut self, generations: usize) {
+        (0..generations).for_each(|_| {
+            self.step();
+        });
+    }
+
+    /// Step until a step changes nothing, returning how many steps that took
+    /// (counting the last).
+    pub fn run_until_stable(&mut self) -> usize {
+        let start = self.generation;
+        while self.step() {}
+        self.generation - start
+    }
+}
+
+impl<T, R> Automaton<T, R>
+where
+    T: Clone + Eq + Hash,
+    R: Fn(&T, &[&T]) -> T,
+{
+    /// Step until the automaton gets back to a state it has been in before,
+    /// returning the generation the cycle starts at and its period (1 for a
+    /// fixed point). Every state along the way is kept to compare against.
+    /// With a background, only the cells that differ from it, and where they
+    /// are, make up the state, so a pattern that moves or grows forever
+    /// never repeats.
+    pub fn find_cycle(&mut self) -> (usize, usize) {
+        let mut seen = HashMap::new();
+        let mut origin = (0, 0);
+        loop {
+            let state = (self.pattern(origin), self.boundary.clone());
+            if let Some(start) = seen.insert(state, self.generation) {
+                return (start, self.generation - start);
+            }
+            let margin = self.margin();
+            origin = (origin.0 - margin, origin.1 - margin);
+            self.step();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_automaton {
+    use super::*;
+
+    fn life(cell: &bool, neighbours: &[&bool]) -> bool {
+        let alive = neighbours.iter().filter(|&&&n| n).count();
+        alive == 3 || (*cell && alive == 2)
+    }
+
+    fn pattern(size: usize, cells: &[(usize, usize)]) -> Grid<bool> {
+        Grid::from_fn(size, size, |point| cells.contains(&point))
+    }
+
+    #[test]
+    fn test_automaton_boundaries() {
+        let block = pattern(4, &[(1, 1), (2, 1), (1, 2), (2, 2)]);
+        let mut automaton = Automaton::new(block.clone(), &MOORE, Boundary::Fixed, life);
+        assert_eq!(automaton.run_until_stable(), 1);
+        assert_eq!(automaton.grid(), &block);
+
+        let blinker = pattern(5, &[(1, 2), (2, 2), (3, 2)]);
+        let mut automaton = Automaton::new(blinker, &MOORE, Boundary::Fixed, life);
+        assert_eq!(automaton.find_cycle(), (0, 2));
+
+        // a glider crosses an 8x8 torus in 4 * 8 generations
+        let glider = pattern(8, &[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+        let mut automaton = Automaton::new(glider, &MOORE, Boundary::Wrapping, life);
+        automaton.run(3);
+        assert_eq!(automaton.find_cycle(), (3, 32));
+
+        // anything next to a live cell comes alive, and the background flips
+        let spread = |cell: &bool, neighbours: &[&bool]| *cell || neighbours.iter().any(|&&n| n);
+        let dot = pattern(1, &[(0, 0)]);
+        let mut automaton = Automaton::new(dot, &VON_NEUMANN, Boundary::Background(false), spread);
+        automaton.run(2);
+        assert_eq!(automaton.grid().width(), 5);
+        assert_eq!(automaton.grid().iter().filter(|&&b| b).count(), 13);
+        let flip = |cell: &bool, _: &[&bool]| !cell;
+        let mut automaton = Automaton::new(pattern(1, &[]), &[], Boundary::Background(false), flip);
+        assert_eq!(automaton.find_cycle(), (0, 2));
+        assert_eq!(automaton.background(), Some(&false));
+    }
+
+    #[test]
+    fn test_automaton_background_cycles() {
+        // the grid grows every step, but the blinker in it still repeats
+        let blinker = pattern(3, &[(0, 1), (1, 1), (2, 1)]);
+        let mut automaton = Automaton::new(blinker, &MOORE, Boundary::Background(false), life);
+        assert_eq!(automaton.find_cycle(), (0, 2));
+        assert_eq!(automaton.grid().width(), 3 + 2 * 2);
+
+        // the lone cell dies and then the corner fills in to make a block,
+        // where on its own the cell leaves just background
+        let corner = pattern(3, &[(0, 0), (1, 0), (0, 1), (2, 2)]);
+        let mut automaton =
+            Automaton::new(corner.clone(), &MOORE, Boundary::Background(false), life);
+        assert_eq!(automaton.find_cycle(), (2, 1));
+        let mut automaton = Automaton::new(corner, &MOORE, Boundary::Background(false), life);
+        assert_eq!(automaton.run_until_stable(), 3);
+        let dot = pattern(1, &[(0, 0)]);
+        let mut automaton = Automaton::new(dot, &MOORE, Boundary::Background(false), life);
+        assert_eq!(automaton.find_cycle(), (1, 1));
+
+        // the same shape somewhere else isn't the same state: a glider's
+        // shape repeats every 4 generations, but it has moved by then
+        let glider = pattern(3, &[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+        let mut automaton = Automaton::new(glider, &MOORE, Boundary::Background(false), life);
+        automaton.run(4);
+        let origin = (-4, -4);
+        let (shape, at) = automaton.pattern(origin);
+        assert_eq!(shape.iter().filter(|&&b| b).count(), 5);
+        assert_eq!(at, (1, 1));
+    }
+}
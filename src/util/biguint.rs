@@ -0,0 +1,210 @@
+use super::Scalar;
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display, Formatter},
+    ops::{Add, AddAssign, Mul, Sub},
+};
+
+/// An arbitrarily large unsigned integer, for counts that can outgrow `u64`.
+#[derive(Clone, Default, PartialEq, Eq, Hash, Debug)]
+pub struct BigUint {
+    // little-endian, without trailing zeros, so zero has no limbs
+    limbs: Vec<u64>,
+}
+
+impl BigUint {
+    pub fn new() -> Self {
+        Self { limbs: Vec::new() }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    pub fn to_u64(&self) -> Option<u64> {
+        match self.limbs[..] {
+            [] => Some(0),
+            [n] => Some(n),
+            _ => None,
+        }
+    }
+
+    fn normalize(mut self) -> Self {
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+        self
+    }
+
+    /// Divide in place by a single limb, returning the remainder.
+    fn div_rem_small(&mut self, divisor: u64) -> u64 {
+        let mut remainder = 0;
+        for limb in self.limbs.iter_mut().rev() {
+            let n = (remainder as u128) << 64 | *limb as u128;
+            *limb = (n / divisor as u128) as u64;
+            remainder = (n % divisor as u128) as u64;
+        }
+        *self = std::mem::take(self).normalize();
+        remainder
+    }
+}
+
+impl From<u64> for BigUint {
+    fn from(n: u64) -> Self {
+        Self { limbs: vec![n] }.normalize()
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (a, b) = (&self.limbs, &other.limbs);
+        a.len()
+            .cmp(&b.len())
+            .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl AddAssign<&BigUint> for BigUint {
+    fn add_assign(&mut self, other: &BigUint) {
+        if other.limbs.len() > self.limbs.len() {
+            self.limbs.resize(other.limbs.len(), 0);
+        }
+        let mut carry = false;
+        for (i, limb) in self.limbs.iter_mut().enumerate() {
+            let (sum, overflow1) = limb.overflowing_add(other.limbs.get(i).copied().unwrap_or(0));
+            let (sum, overflow2) = sum.overflowing_add(carry as u64);
+            *limb = sum;
+            carry = overflow1 || overflow2;
+            if !carry && i >= other.limbs.len() {
+                break;
+            }
+        }
+        if carry {
+            self.limbs.push(1);
+        }
+    }
+}
+
+impl AddAssign for BigUint {
+    fn add_assign(&mut self, other: Self) {
+        *self += &other;
+    }
+}
+
+impl Add for BigUint {
+    type Output = Self;
+
+    fn add(mut self, other: Self) -> Self {
+        self += &other;
+        self
+    }
+}
+
+impl Sub for BigUint {
+    type Output = Self;
+
+    fn sub(mut self, other: Self) -> Self {
+        assert!(self >= other, "attempt to subtract with overflow");
+        let mut borrow = false;
+        for (i, limb) in self.limbs.iter_mut().enumerate() {
+            let (diff, overflow1) = limb.overflowing_sub(other.limbs.get(i).copied().unwrap_or(0));
+            let (diff, overflow2) = diff.overflowing_sub(borrow as u64);
+            *limb = diff;
+            borrow = overflow1 || overflow2;
+        }
+        self.normalize()
+    }
+}
+
+impl Mul for &BigUint {
+    type Output = BigUint;
+
+    fn mul(self, other: &BigUint) -> BigUint {
+        let mut limbs = vec![0; self.limbs.len() + other.limbs.len()];
+        for (i, &a) in self.limbs.iter().enumerate() {
+            let mut carry = 0;
+            for (j, &b) in other.limbs.iter().enumerate() {
+                let n = a as u128 * b as u128 + limbs[i + j] as u128 + carry;
+                limbs[i + j] = n as u64;
+                carry = n >> 64;
+            }
+            limbs[i + other.limbs.len()] = carry as u64;
+        }
+        BigUint { limbs }.normalize()
+    }
+}
+
+impl Mul for BigUint {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        &self * &other
+    }
+}
+
+impl Scalar for BigUint {
+    fn zero() -> Self {
+        Self::new()
+    }
+
+    fn one() -> Self {
+        Self::from(1)
+    }
+
+    fn checked_add(self, other: Self) -> Option<Self> {
+        Some(self + other)
+    }
+
+    fn checked_mul(self, other: Self) -> Option<Self> {
+        Some(self * other)
+    }
+}
+
+impl Display for BigUint {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        const CHUNK: u64 = 10_000_000_000_000_000_000;
+        let mut n = self.clone();
+        let mut chunks = vec![n.div_rem_small(CHUNK)];
+        while !n.is_zero() {
+            chunks.push(n.div_rem_small(CHUNK));
+        }
+        write!(f, "{}", chunks.pop().unwrap())?;
+        chunks
+            .iter()
+            .rev()
+            .try_for_each(|chunk| write!(f, "{:019}", chunk))
+    }
+}
+
+#[cfg(test)]
+mod test_biguint {
+    use super::*;
+
+    #[test]
+    fn test_biguint_arithmetic() {
+        let two = BigUint::from(2);
+        let power = (0..200).fold(BigUint::one(), |n, _| &n * &two);
+        assert_eq!(
+            power.to_string(),
+            "1606938044258990275541962092341162602522202993782792835301376"
+        );
+        let factorial = (1..=30)
+            .map(BigUint::from)
+            .fold(BigUint::one(), |n, m| n * m);
+        assert_eq!(factorial.to_string(), "265252859812191058636308480000000");
+        let max = BigUint::from(u64::MAX);
+        let wrapped = max.clone() + BigUint::one();
+        assert_eq!(wrapped.to_string(), "18446744073709551616");
+        assert_eq!(wrapped.to_u64(), None);
+        let difference = factorial.clone() - wrapped.clone();
+        assert_eq!(difference.to_string(), "265252859812172611892234770448384");
+        assert_eq!(wrapped - BigUint::one(), max);
+        assert!(factorial > power.clone() - power && BigUint::new().to_string() == "0");
+    }
+}
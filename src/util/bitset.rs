@@ -0,0 +1,267 @@
+use std::{
+    fmt::{self, Debug, Formatter},
+    hash::{Hash, Hasher},
+    iter::FromIterator,
+    ops::{BitAnd, BitOr, Sub},
+};
+
+/// A set of small integers, stored as one bit per possible value, so it's
+/// only sensible when the values are dense enough.
+#[derive(Clone, Default)]
+pub struct BitSet(Vec<u64>);
+
+impl BitSet {
+    fn unpack(value: u32) -> (usize, usize) {
+        ((value / 64) as usize, (value & 63) as usize)
+    }
+
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    /// An empty set with room for the values `0..capacity` without growing.
+    pub fn with_capacity(capacity: u32) -> Self {
+        Self(Vec::with_capacity((capacity as usize).div_ceil(64)))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.iter().map(|block| block.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|&block| block == 0)
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    pub fn contains(&self, value: u32) -> bool {
+        let (chunk, index) = Self::unpack(value);
+        self.0
+            .get(chunk)
+            .is_some_and(|block| block & 1 << index != 0)
+    }
+
+    /// Add `value` to the set, returning whether it wasn't already present.
+    pub fn insert(&mut self, value: u32) -> bool {
+        let (chunk, index) = Self::unpack(value);
+        if chunk >= self.0.len() {
+            self.0.resize(chunk + 1, 0);
+        }
+        let absent = self.0[chunk] & 1 << index == 0;
+        self.0[chunk] |= 1 << index;
+        absent
+    }
+
+    /// Remove `value` from the set, returning whether it was present.
+    pub fn remove(&mut self, value: u32) -> bool {
+        let (chunk, index) = Self::unpack(value);
+        match self.0.get_mut(chunk) {
+            Some(block) if *block & 1 << index != 0 => {
+                *block &= !(1 << index);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// The values in the set, in ascending order.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            blocks: &self.0,
+            chunk: 0,
+            block: self.0.first().copied().unwrap_or(0),
+        }
+    }
+
+    pub fn union_with(&mut self, other: &Self) {
+        if other.0.len() > self.0.len() {
+            self.0.resize(other.0.len(), 0);
+        }
+        self.0.iter_mut().zip(&other.0).for_each(|(a, b)| *a |= b);
+    }
+
+    pub fn intersect_with(&mut self, other: &Self) {
+        self.0.truncate(other.0.len());
+        self.0.iter_mut().zip(&other.0).for_each(|(a, b)| *a &= b);
+    }
+
+    pub fn difference_with(&mut self, other: &Self) {
+        self.0.iter_mut().zip(&other.0).for_each(|(a, b)| *a &= !b);
+    }
+
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.0
+            .iter()
+            .enumerate()
+            .all(|(i, a)| a & !other.0.get(i).unwrap_or(&0) == 0)
+    }
+
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.0.iter().zip(&other.0).all(|(a, b)| a & b == 0)
+    }
+
+    /// The blocks up to the last non-empty one, so that sets with the same
+    /// values compare equal however much room they've grown.
+    fn trimmed(&self) -> &[u64] {
+        let len = self
+            .0
+            .iter()
+            .rposition(|&block| block != 0)
+            .map_or(0, |i| i + 1);
+        &self.0[..len]
+    }
+}
+
+pub struct Iter<'a> {
+    blocks: &'a [u64],
+    chunk: usize,
+    block: u64,
+}
+
+impl Iterator for Iter<'_> {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        while self.block == 0 {
+            self.chunk += 1;
+            self.block = *self.blocks.get(self.chunk)?;
+        }
+        let index = self.block.trailing_zeros();
+        self.block &= self.block - 1;
+        Some(self.chunk as u32 * 64 + index)
+    }
+}
+
+impl<'a> IntoIterator for &'a BitSet {
+    type Item = u32;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+impl FromIterator<u32> for BitSet {
+    fn from_iter<I: IntoIterator<Item = u32>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<u32> for BitSet {
+    fn extend<I: IntoIterator<Item = u32>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|value| {
+            self.insert(value);
+        });
+    }
+}
+
+impl PartialEq for BitSet {
+    fn eq(&self, other: &Self) -> bool {
+        self.trimmed() == other.trimmed()
+    }
+}
+
+impl Eq for BitSet {}
+
+impl Hash for BitSet {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.trimmed().hash(state);
+    }
+}
+
+impl Debug for BitSet {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl BitOr for &BitSet {
+    type Output = BitSet;
+
+    fn bitor(self, other: &BitSet) -> BitSet {
+        let mut set = self.clone();
+        set.union_with(other);
+        set
+    }
+}
+
+impl BitAnd for &BitSet {
+    type Output = BitSet;
+
+    fn bitand(self, other: &BitSet) -> BitSet {
+        let mut set = self.clone();
+        set.intersect_with(other);
+        set
+    }
+}
+
+impl Sub for &BitSet {
+    type Output = BitSet;
+
+    fn sub(self, other: &BitSet) -> BitSet {
+        let mut set = self.clone();
+        set.difference_with(other);
+        set
+    }
+}
+
+#[cfg(test)]
+mod test_bitset {
+    use super::*;
+    use crate::util::Random;
+    use hashbrown::HashSet;
+
+    fn sorted(set: &HashSet<u32>) -> Vec<u32> {
+        let mut values = set.iter().copied().collect::<Vec<_>>();
+        values.sort_unstable();
+        values
+    }
+
+    #[test]
+    fn test_bitset_against_hashset() {
+        let mut random = Random::new(0x2545_f491);
+        let (mut sets, mut references) = (vec![], vec![]);
+        for _ in 0..4 {
+            let (mut set, mut reference) = (BitSet::new(), HashSet::new());
+            for _ in 0..500 {
+                let value = random.below(300) as u32;
+                match random.below(3) {
+                    0 => assert_eq!(set.remove(value), reference.remove(&value)),
+                    _ => assert_eq!(set.insert(value), reference.insert(value)),
+                }
+                let probe = random.below(400) as u32;
+                assert_eq!(set.contains(probe), reference.contains(&probe));
+            }
+            assert_eq!(set.len(), reference.len());
+            assert_eq!(set.iter().collect::<Vec<_>>(), sorted(&reference));
+            sets.push(set);
+            references.push(reference);
+        }
+        for (i, j) in [(0, 1), (1, 2), (2, 3), (3, 0)] {
+            let (a, b) = (&sets[i], &sets[j]);
+            let (ra, rb) = (&references[i], &references[j]);
+            assert_eq!((a | b).iter().collect::<Vec<_>>(), sorted(&(ra | rb)));
+            assert_eq!((a & b).iter().collect::<Vec<_>>(), sorted(&(ra & rb)));
+            assert_eq!((a - b).iter().collect::<Vec<_>>(), sorted(&(ra - rb)));
+            assert_eq!(a.is_disjoint(b), ra.is_disjoint(rb));
+            assert!((a & b).is_subset(a) && a.is_subset(&(a | b)));
+        }
+    }
+
+    #[test]
+    fn test_bitset_equality() {
+        let mut a = [3, 200, 64].iter().copied().collect::<BitSet>();
+        let b = [64, 3].iter().copied().collect::<BitSet>();
+        assert_ne!(a, b);
+        a.remove(200);
+        assert_eq!(a, b);
+        assert_eq!(format!("{:?}", a), "{3, 64}");
+        a.clear();
+        assert!(a.is_empty());
+        assert_eq!(a, BitSet::with_capacity(1000));
+    }
+}
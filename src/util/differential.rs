@@ -0,0 +1,175 @@
+use super::Random;
+use crate::day::Solver;
+use std::{
+    fmt::Debug,
+    panic::{self, AssertUnwindSafe},
+};
+
+/// Cases that can be made smaller, to narrow down why they fail.
+pub trait Shrink: Sized {
+    /// Some simpler versions of the case, simplest first.
+    fn shrink(&self) -> Vec<Self>;
+}
+
+impl Shrink for i64 {
+    fn shrink(&self) -> Vec<Self> {
+        let mut smaller = vec![0, self / 2, self - self.signum()];
+        smaller.dedup();
+        smaller.retain(|n| n != self);
+        smaller
+    }
+}
+
+impl Shrink for u8 {
+    fn shrink(&self) -> Vec<Self> {
+        let smaller = (*self as i64).shrink();
+        smaller.into_iter().map(|n| n as u8).collect()
+    }
+}
+
+impl Shrink for bool {
+    fn shrink(&self) -> Vec<Self> {
+        if *self {
+            vec![false]
+        } else {
+            vec![]
+        }
+    }
+}
+
+impl<T: Shrink + Clone> Shrink for Vec<T> {
+    fn shrink(&self) -> Vec<Self> {
+        let mut smaller = Vec::new();
+        // cut out big chunks first, then single elements
+        let mut chunk = self.len() / 2;
+        while chunk > 0 {
+            for start in (0..self.len()).step_by(chunk) {
+                let end = (start + chunk).min(self.len());
+                smaller.push([&self[..start], &self[end..]].concat());
+            }
+            chunk /= 2;
+        }
+        for (i, element) in self.iter().enumerate() {
+            for simpler in element.shrink() {
+                let mut vec = self.clone();
+                vec[i] = simpler;
+                smaller.push(vec);
+            }
+        }
+        smaller
+    }
+}
+
+impl<T: Shrink + Clone, const N: usize> Shrink for [T; N] {
+    fn shrink(&self) -> Vec<Self> {
+        let mut smaller = Vec::new();
+        for (i, element) in self.iter().enumerate() {
+            for simpler in element.shrink() {
+                let mut array = self.clone();
+                array[i] = simpler;
+                smaller.push(array);
+            }
+        }
+        smaller
+    }
+}
+
+impl<A: Shrink + Clone, B: Shrink + Clone> Shrink for (A, B) {
+    fn shrink(&self) -> Vec<Self> {
+        let firsts = self.0.shrink().into_iter().map(|a| (a, self.1.clone()));
+        let seconds = self.1.shrink().into_iter().map(|b| (self.0.clone(), b));
+        firsts.chain(seconds).collect()
+    }
+}
+
+fn holds<T, P: Fn(&T) -> Result<(), String>>(property: &P, case: &T) -> Result<(), String> {
+    match panic::catch_unwind(AssertUnwindSafe(|| property(case))) {
+        Ok(result) => result,
+        Err(payload) => {
+            let message = match payload.downcast::<String>() {
+                Ok(message) => *message,
+                Err(payload) => payload
+                    .downcast::<&str>()
+                    .map_or(String::new(), |m| m.to_string()),
+            };
+            Err(format!("panicked: {}", message))
+        }
+    }
+}
+
+/// Check `property` on `cases` cases made by `generate`, panicking with the
+/// smallest failing case that shrinking can find if any of them fails. A
+/// panic counts as failing. The property should pass cases it doesn't
+/// apply to, since shrinking can make any case.
+pub fn check<T, G, P>(cases: usize, seed: u64, mut generate: G, property: P)
+where
+    T: Shrink + Debug,
+    G: FnMut(&mut Random) -> T,
+    P: Fn(&T) -> Result<(), String>,
+{
+    let mut random = Random::new(seed);
+    for _ in 0..cases {
+        let case = generate(&mut random);
+        if let Err(message) = holds(&property, &case) {
+            let (mut case, mut message) = (case, message);
+            'shrinking: loop {
+                for smaller in case.shrink() {
+                    if let Err(smaller_message) = holds(&property, &smaller) {
+                        case = smaller;
+                        message = smaller_message;
+                        continue 'shrinking;
+                    }
+                }
+                break;
+            }
+            panic!("failed on {:?}: {}", case, message);
+        }
+    }
+}
+
+/// Whether `solver` agrees with `reference` on both parts for `input`.
+pub fn agree(
+    input: &str,
+    solver: Solver,
+    reference: fn(&str) -> [String; 2],
+) -> Result<(), String> {
+    let solution = solver(input);
+    let expected = reference(input);
+    if [&solution.part1, &solution.part2] == [&expected[0], &expected[1]] {
+        Ok(())
+    } else {
+        Err(format!(
+            "solved {:?} but the reference gives {:?} for\n{}",
+            [solution.part1, solution.part2],
+            expected,
+            input
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test_differential {
+    use super::*;
+
+    #[test]
+    fn test_differential_shrinks() {
+        // fails on any list with a sum over 100, and shrinks until it's only
+        // just over
+        let result = panic::catch_unwind(|| {
+            check(
+                50,
+                1,
+                |random| (0..10).map(|_| random.range(0..=50)).collect::<Vec<_>>(),
+                |list| match list.iter().sum::<i64>() {
+                    sum if sum > 100 => Err(format!("sum {}", sum)),
+                    _ => Ok(()),
+                },
+            )
+        });
+        let message = *result.unwrap_err().downcast::<String>().unwrap();
+        assert!(message.starts_with("failed on [") && message.ends_with(": sum 101"));
+
+        let passing = panic::catch_unwind(|| check(50, 1, |random| random.flip(), |_| Ok(())));
+        assert!(passing.is_ok());
+    }
+}
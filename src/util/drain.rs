@@ -0,0 +1,339 @@
+use hashbrown::HashMap;
+use std::{
+    collections::VecDeque,
+    hash::{BuildHasher, Hash},
+    marker::PhantomData,
+    ops::Range,
+    thread,
+};
+
+pub trait DrainFilterMappable<T, U> {
+    /// Remove the elements for which `filter` returns `Some(U)`, lazily,
+    /// iterating over the returned `U`s. Dropping the iterator early still
+    /// filters the rest. If `filter` panics, the element it panicked on and
+    /// those after it are kept, and the removed ones are dropped, once each.
+    fn drain_filter_map<F>(&mut self, filter: F) -> impl Iterator<Item = U>
+    where
+        F: FnMut(&mut T) -> Option<U>;
+}
+
+/// Sequences the filter can walk through by index.
+trait Slots<T> {
+    fn slot(&mut self, index: usize) -> &mut T;
+    fn swap(&mut self, i: usize, j: usize);
+    fn remove_range(&mut self, range: Range<usize>);
+}
+
+impl<T> Slots<T> for Vec<T> {
+    fn slot(&mut self, index: usize) -> &mut T {
+        &mut self[index]
+    }
+
+    fn swap(&mut self, i: usize, j: usize) {
+        self[..].swap(i, j);
+    }
+
+    fn remove_range(&mut self, range: Range<usize>) {
+        self.drain(range);
+    }
+}
+
+impl<T> Slots<T> for VecDeque<T> {
+    fn slot(&mut self, index: usize) -> &mut T {
+        &mut self[index]
+    }
+
+    fn swap(&mut self, i: usize, j: usize) {
+        self.swap(i, j);
+    }
+
+    fn remove_range(&mut self, range: Range<usize>) {
+        self.drain(range);
+    }
+}
+
+/// How far a drain has got. The elements before `index` have been through
+/// the filter, kept ones shuffled down past the `removed` removed ones,
+/// which wait just before `index` until the drain is over. So the sequence
+/// is always whole, and nothing is dropped twice whenever it panics.
+struct Sweep {
+    index: usize,
+    removed: usize,
+    len: usize,
+    filtering: bool,
+}
+
+impl Sweep {
+    fn new(len: usize) -> Self {
+        Self {
+            index: 0,
+            removed: 0,
+            len,
+            filtering: false,
+        }
+    }
+
+    fn next<T, U, S, F>(&mut self, slots: &mut S, filter: &mut F) -> Option<U>
+    where
+        S: Slots<T>,
+        F: FnMut(&mut T) -> Option<U>,
+    {
+        while self.index < self.len {
+            let i = self.index;
+            self.filtering = true;
+            let mapped = filter(slots.slot(i));
+            self.filtering = false;
+            self.index += 1;
+            match mapped {
+                Some(mapped) => {
+                    self.removed += 1;
+                    return Some(mapped);
+                }
+                None => slots.swap(i - self.removed, i),
+            }
+        }
+        None
+    }
+
+    /// Filter whatever is left, unless this is running because of a panic,
+    /// then drop the removed elements.
+    fn finish<T, U, S, F>(&mut self, slots: &mut S, filter: &mut F)
+    where
+        S: Slots<T>,
+        F: FnMut(&mut T) -> Option<U>,
+    {
+        if !self.filtering && !thread::panicking() {
+            while self.next(slots, filter).is_some() {}
+        }
+        slots.remove_range(self.index - self.removed..self.index);
+    }
+}
+
+struct DrainFilterMap<'a, S, T, U, F>
+where
+    S: Slots<T>,
+    F: FnMut(&mut T) -> Option<U>,
+{
+    slots: &'a mut S,
+    sweep: Sweep,
+    filter: F,
+    mapping: PhantomData<fn(&mut T) -> Option<U>>,
+}
+
+impl<'a, S, T, U, F> Iterator for DrainFilterMap<'a, S, T, U, F>
+where
+    S: Slots<T>,
+    F: FnMut(&mut T) -> Option<U>,
+{
+    type Item = U;
+
+    fn next(&mut self) -> Option<U> {
+        self.sweep.next(self.slots, &mut self.filter)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.sweep.len - self.sweep.index))
+    }
+}
+
+impl<'a, S, T, U, F> Drop for DrainFilterMap<'a, S, T, U, F>
+where
+    S: Slots<T>,
+    F: FnMut(&mut T) -> Option<U>,
+{
+    fn drop(&mut self) {
+        self.sweep.finish(self.slots, &mut self.filter);
+    }
+}
+
+impl<T, U> DrainFilterMappable<T, U> for Vec<T> {
+    fn drain_filter_map<F>(&mut self, filter: F) -> impl Iterator<Item = U>
+    where
+        F: FnMut(&mut T) -> Option<U>,
+    {
+        DrainFilterMap {
+            sweep: Sweep::new(self.len()),
+            slots: self,
+            filter,
+            mapping: PhantomData,
+        }
+    }
+}
+
+impl<T, U> DrainFilterMappable<T, U> for VecDeque<T> {
+    fn drain_filter_map<F>(&mut self, filter: F) -> impl Iterator<Item = U>
+    where
+        F: FnMut(&mut T) -> Option<U>,
+    {
+        DrainFilterMap {
+            sweep: Sweep::new(self.len()),
+            slots: self,
+            filter,
+            mapping: PhantomData,
+        }
+    }
+}
+
+/// A map's entries are taken out to drain, and the ones kept are put back
+/// when it's over (re-hashed, so the filter may change keys). Kept entries
+/// the filter gives the same key collapse into one, as with `extend`: the
+/// one the filter saw last wins, and the others are dropped.
+struct MapDrainFilterMap<'a, K, V, H, U, F>
+where
+    K: Eq + Hash,
+    H: BuildHasher,
+    F: FnMut(&mut (K, V)) -> Option<U>,
+{
+    map: &'a mut HashMap<K, V, H>,
+    entries: Vec<(K, V)>,
+    sweep: Sweep,
+    filter: F,
+}
+
+impl<'a, K, V, H, U, F> Iterator for MapDrainFilterMap<'a, K, V, H, U, F>
+where
+    K: Eq + Hash,
+    H: BuildHasher,
+    F: FnMut(&mut (K, V)) -> Option<U>,
+{
+    type Item = U;
+
+    fn next(&mut self) -> Option<U> {
+        self.sweep.next(&mut self.entries, &mut self.filter)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.sweep.len - self.sweep.index))
+    }
+}
+
+impl<'a, K, V, H, U, F> Drop for MapDrainFilterMap<'a, K, V, H, U, F>
+where
+    K: Eq + Hash,
+    H: BuildHasher,
+    F: FnMut(&mut (K, V)) -> Option<U>,
+{
+    fn drop(&mut self) {
+        self.sweep.finish(&mut self.entries, &mut self.filter);
+        self.map.extend(self.entries.drain(..));
+    }
+}
+
+impl<K, V, H, U> DrainFilterMappable<(K, V), U> for HashMap<K, V, H>
+where
+    K: Eq + Hash,
+    H: BuildHasher,
+{
+    fn drain_filter_map<F>(&mut self, filter: F) -> impl Iterator<Item = U>
+    where
+        F: FnMut(&mut (K, V)) -> Option<U>,
+    {
+        let entries = self.drain().collect::<Vec<_>>();
+        MapDrainFilterMap {
+            sweep: Sweep::new(entries.len()),
+            map: self,
+            entries,
+            filter,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_drain {
+    use super::*;
+    use std::{
+        panic::{self, AssertUnwindSafe},
+        rc::Rc,
+    };
+
+    #[test]
+    fn test_drain_filter_map() {
+        let mut vec = (0..10).collect::<Vec<_>>();
+        let evens = vec.drain_filter_map(|&mut n| (n % 2 == 0).then_some(n * 10));
+        assert_eq!(evens.collect::<Vec<_>>(), [0, 20, 40, 60, 80]);
+        assert_eq!(vec, [1, 3, 5, 7, 9]);
+        // dropped after taking one, but still removes the rest
+        assert_eq!(
+            vec.drain_filter_map(|&mut n| (n > 2).then_some(n)).next(),
+            Some(3)
+        );
+        assert_eq!(vec, [1]);
+
+        let mut deque = (0..10).collect::<VecDeque<_>>();
+        deque.rotate_left(7);
+        let small = deque.drain_filter_map(|n| (*n < 3).then_some(*n));
+        assert_eq!(small.collect::<Vec<_>>(), [0, 1, 2]);
+        assert_eq!(deque, [7, 8, 9, 3, 4, 5, 6]);
+
+        let mut map = (0..10).map(|n| (n, n * n)).collect::<HashMap<_, _>>();
+        let mut odd = map
+            .drain_filter_map(|&mut (k, v)| (k % 2 == 1).then_some(v))
+            .collect::<Vec<_>>();
+        odd.sort_unstable();
+        assert_eq!(odd, [1, 9, 25, 49, 81]);
+        assert_eq!(map.len(), 5);
+        assert!(map.iter().all(|(k, v)| k % 2 == 0 && k * k == *v));
+        // the kept entries go back under their new keys
+        drop(map.drain_filter_map(|(k, _)| {
+            *k += 100;
+            None::<()>
+        }));
+        assert_eq!(map.get(&104), Some(&16));
+        // and ones sharing a new key overwrite each other
+        drop(map.drain_filter_map(|(k, _)| {
+            *k = 0;
+            None::<()>
+        }));
+        assert_eq!(map.len(), 1);
+        assert!([0, 4, 16, 36, 64].contains(&map[&0]));
+    }
+
+    #[test]
+    fn test_drain_filter_map_panics() {
+        // each element holds a clone of `token`, so it counts them, and a
+        // leak would leave the count too high
+        let token = Rc::new(());
+        let filter = |panic_at: usize| {
+            move |(n, _): &mut (usize, Rc<()>)| {
+                assert!(*n != panic_at, "filter panicked");
+                (*n % 3 == 0).then_some(*n)
+            }
+        };
+        let elements = || (0..10).map(|n| (n, Rc::clone(&token)));
+
+        let mut vec = elements().collect::<Vec<_>>();
+        let removed = panic::catch_unwind(AssertUnwindSafe(|| {
+            let mut drain = vec.drain_filter_map(filter(7));
+            assert_eq!(drain.next(), Some(0));
+            drain.count()
+        }));
+        assert!(removed.is_err());
+        let left = vec.iter().map(|&(n, _)| n).collect::<Vec<_>>();
+        assert_eq!(left, [1, 2, 4, 5, 7, 8, 9]);
+        assert_eq!(Rc::strong_count(&token), 1 + 7);
+
+        // a panic in the code using the iterator stops the filtering
+        let mut deque = elements().collect::<VecDeque<_>>();
+        let consumer = panic::catch_unwind(AssertUnwindSafe(|| {
+            for n in deque.drain_filter_map(filter(usize::MAX)) {
+                assert!(n < 3, "consumer panicked");
+            }
+        }));
+        assert!(consumer.is_err());
+        let left = deque.iter().map(|&(n, _)| n).collect::<Vec<_>>();
+        assert_eq!(left, [1, 2, 4, 5, 6, 7, 8, 9]);
+
+        let mut map = elements()
+            .map(|(n, rc)| (n, (n, rc)))
+            .collect::<HashMap<_, _>>();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            map.drain_filter_map(|(_, element)| filter(5)(element))
+                .count()
+        }));
+        assert!(result.is_err());
+        // which multiples of 3 came before 5 depends on the map's order
+        assert!((0..10).all(|n| n % 3 == 0 || map.contains_key(&n)));
+        drop((vec, deque, map));
+        assert_eq!(Rc::strong_count(&token), 1);
+    }
+}
@@ -0,0 +1,252 @@
+use super::{Cursor, ParseResult};
+use hashbrown::HashMap;
+use std::{
+    fmt::{Display, Write},
+    hash::Hash,
+    ops::{Add, Mul},
+};
+
+/// A directed multigraph with labelled nodes and weighted edges. Nodes are
+/// numbered in the order they were added, and parallel edges are kept apart
+/// until they are merged.
+#[derive(Clone, Debug)]
+pub struct Graph<L, W = u32> {
+    labels: Vec<L>,
+    ids: HashMap<L, usize>,
+    // the edges out of each node, empty for a node that has been contracted
+    edges: Vec<Vec<(usize, W)>>,
+    contracted: Vec<bool>,
+}
+
+impl<L: Clone + Eq + Hash, W: Copy + Add<Output = W> + Mul<Output = W>> Graph<L, W> {
+    pub fn new() -> Self {
+        Self {
+            labels: Vec::new(),
+            ids: HashMap::new(),
+            edges: Vec::new(),
+            contracted: Vec::new(),
+        }
+    }
+
+    /// The node labelled `label`, added if there isn't one yet.
+    pub fn add_node(&mut self, label: L) -> usize {
+        if let Some(&id) = self.ids.get(&label) {
+            return id;
+        }
+        let id = self.labels.len();
+        self.labels.push(label.clone());
+        self.ids.insert(label, id);
+        self.edges.push(Vec::new());
+        self.contracted.push(false);
+        id
+    }
+
+    pub fn add_edge(&mut self, from: usize, to: usize, weight: W) {
+        assert!(
+            !self.contracted[from] && !self.contracted[to],
+            "edge to a contracted node"
+        );
+        self.edges[from].push((to, weight));
+    }
+
+    /// An edge each way between `a` and `b`.
+    pub fn add_undirected_edge(&mut self, a: usize, b: usize, weight: W) {
+        self.add_edge(a, b, weight);
+        self.add_edge(b, a, weight);
+    }
+
+    /// The node labelled `label`, unless there isn't one or it has been
+    /// contracted.
+    pub fn node(&self, label: &L) -> Option<usize> {
+        self.ids.get(label).copied()
+    }
+
+    pub fn label(&self, node: usize) -> &L {
+        &self.labels[node]
+    }
+
+    /// The nodes that haven't been contracted, in order.
+    pub fn nodes(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.labels.len()).filter(move |&node| !self.contracted[node])
+    }
+
+    /// The edges out of `node` as `(to, weight)`, parallel ones separately.
+    pub fn edges(&self, node: usize) -> impl Iterator<Item = (usize, W)> + '_ {
+        self.edges[node].iter().copied()
+    }
+
+    /// The total weight of the edges from `from` to `to`, if there are any.
+    pub fn weight(&self, from: usize, to: usize) -> Option<W> {
+        self.edges(from)
+            .filter(|&(next, _)| next == to)
+            .map(|(_, weight)| weight)
+            .reduce(|a, b| a + b)
+    }
+
+    /// Replace each set of parallel edges with one edge of their total
+    /// weight, ordered by where it goes.
+    pub fn merge_parallel_edges(&mut self) {
+        for edges in &mut self.edges {
+            let mut merged = HashMap::<usize, W>::new();
+            for &(to, weight) in edges.iter() {
+                merged
+                    .entry(to)
+                    .and_modify(|total| *total = *total + weight)
+                    .or_insert(weight);
+            }
+            *edges = merged.into_iter().collect();
+            edges.sort_unstable_by_key(|&(to, _)| to);
+        }
+    }
+
+    /// Remove `node`, joining every edge into it to every edge out of it
+    /// with an edge weighted by their product, so that walks through it
+    /// are counted in the weights. This includes walks straight back, which
+    /// become loops. `node` mustn't have a loop itself.
+    pub fn contract(&mut self, node: usize) {
+        let outgoing = std::mem::take(&mut self.edges[node]);
+        assert!(
+            outgoing.iter().all(|&(to, _)| to != node),
+            "contracting a loop"
+        );
+        for from in 0..self.edges.len() {
+            let incoming = self.edges[from]
+                .iter()
+                .filter(|&&(to, _)| to == node)
+                .map(|&(_, weight)| weight)
+                .collect::<Vec<_>>();
+            if incoming.is_empty() {
+                continue;
+            }
+            self.edges[from].retain(|&(to, _)| to != node);
+            for weight in incoming {
+                let joined = outgoing
+                    .iter()
+                    .map(|&(to, next_weight)| (to, weight * next_weight));
+                self.edges[from].extend(joined);
+            }
+        }
+        self.contracted[node] = true;
+        self.ids.remove(&self.labels[node]);
+    }
+}
+
+impl<'a, W> Graph<&'a str, W>
+where
+    W: Copy + Add<Output = W> + Mul<Output = W> + From<u8>,
+{
+    /// One undirected edge of weight 1 per line, `a-b`, between nodes
+    /// labelled by words.
+    pub fn parse_edges(cursor: &mut Cursor<'a>) -> ParseResult<Self> {
+        let mut graph = Self::new();
+        cursor.lines(|cursor| {
+            let a = cursor.word()?;
+            cursor.literal("-")?;
+            let b = cursor.word()?;
+            let (a, b) = (graph.add_node(a), graph.add_node(b));
+            graph.add_undirected_edge(a, b, W::from(1));
+            Ok(())
+        })?;
+        Ok(graph)
+    }
+}
+
+impl<L, W> Graph<L, W>
+where
+    L: Clone + Eq + Hash + Display,
+    W: Copy + Add<Output = W> + Mul<Output = W> + PartialEq + Display,
+{
+    /// The graph in Graphviz's DOT language. A pair of edges going opposite
+    /// ways with the same weight is drawn as one edge with two arrowheads.
+    /// Labels are quoted, with any quotes and backslashes in them escaped.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph {\n");
+        for node in self.nodes() {
+            let label = self.labels[node].to_string();
+            let label = label.replace('\\', "\\\\").replace('"', "\\\"");
+            writeln!(dot, "    {} [label=\"{}\"];", node, label).unwrap();
+        }
+        // edges waiting for a reverse to pair up with, by where they go
+        let mut unpaired = HashMap::<(usize, usize), Vec<W>>::new();
+        for from in self.nodes() {
+            for (to, weight) in self.edges(from) {
+                let reverses = unpaired.entry((to, from)).or_default();
+                if let Some(i) = reverses.iter().position(|&w| w == weight) {
+                    reverses.swap_remove(i);
+                    let attributes = format!("dir=both, label={}", weight);
+                    writeln!(dot, "    {} -> {} [{}];", to, from, attributes).unwrap();
+                } else {
+                    unpaired.entry((from, to)).or_default().push(weight);
+                }
+            }
+        }
+        let mut unpaired = unpaired.into_iter().collect::<Vec<_>>();
+        unpaired.sort_unstable_by_key(|&(edge, _)| edge);
+        for ((from, to), weights) in unpaired {
+            for weight in weights {
+                writeln!(dot, "    {} -> {} [label={}];", from, to, weight).unwrap();
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+impl<L, W> Default for Graph<L, W>
+where
+    L: Clone + Eq + Hash,
+    W: Copy + Add<Output = W> + Mul<Output = W>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test_graph {
+    use super::*;
+    use crate::util;
+
+    #[test]
+    fn test_graph_contraction() {
+        let input = "a-X\nX-b\na-b\nX-c\n";
+        let mut graph = util::parse(input, Graph::<_, u32>::parse_edges).unwrap();
+        let [a, x, b, c] = ["a", "X", "b", "c"].map(|label| graph.node(&label).unwrap());
+        assert_eq!(graph.nodes().count(), 4);
+        assert_eq!(graph.weight(x, c), Some(1));
+        assert_eq!(graph.weight(a, c), None);
+
+        graph.contract(x);
+        graph.merge_parallel_edges();
+        assert_eq!(graph.node(&"X"), None);
+        assert_eq!(graph.nodes().collect::<Vec<_>>(), [a, b, c]);
+        // a-b directly or through X, and back through X
+        assert_eq!(graph.edges(a).collect::<Vec<_>>(), [(a, 1), (b, 2), (c, 1)]);
+        assert_eq!(graph.weight(c, c), Some(1));
+
+        // walks through a chain of contracted nodes multiply up
+        let mut chain = Graph::<_, u64>::new();
+        let nodes = (0..5).map(|n| chain.add_node(n)).collect::<Vec<_>>();
+        for pair in nodes.windows(2) {
+            chain.add_edge(pair[0], pair[1], 3);
+        }
+        nodes[1..4].iter().for_each(|&node| chain.contract(node));
+        assert_eq!(chain.weight(nodes[0], nodes[4]), Some(81));
+    }
+
+    #[test]
+    fn test_graph_dot() {
+        let mut graph = util::parse("a-b\nb-c\n", Graph::<_, u32>::parse_edges).unwrap();
+        let (a, c) = (graph.node(&"a").unwrap(), graph.node(&"c").unwrap());
+        graph.add_edge(a, c, 5);
+        let expected = "digraph {\n    0 [label=\"a\"];\n    1 [label=\"b\"];\n    \
+            2 [label=\"c\"];\n    0 -> 1 [dir=both, label=1];\n    \
+            1 -> 2 [dir=both, label=1];\n    0 -> 2 [label=5];\n}\n";
+        assert_eq!(graph.to_dot(), expected);
+
+        let mut quoted = Graph::<_, u32>::new();
+        quoted.add_node(r#"say "hi" \ bye"#);
+        let expected = "digraph {\n    0 [label=\"say \\\"hi\\\" \\\\ bye\"];\n}\n";
+        assert_eq!(quoted.to_dot(), expected);
+    }
+}
@@ -0,0 +1,257 @@
+use itertools::iproduct;
+use std::{
+    fmt::{self, Display, Formatter},
+    iter::StepBy,
+    ops::{Index, IndexMut},
+    slice::{Chunks, ChunksMut, Iter, IterMut},
+};
+
+pub type Point = (usize, usize);
+
+const ORTHOGONAL: [(isize, isize); 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+const SURROUNDING: [(isize, isize); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+
+fn offset_within(
+    (width, height): (usize, usize),
+    (x, y): Point,
+    (dx, dy): (isize, isize),
+) -> Option<Point> {
+    let (x, y) = (x.wrapping_add(dx as usize), y.wrapping_add(dy as usize));
+    (x < width && y < height).then_some((x, y))
+}
+
+fn wrap_within((width, height): (usize, usize), (x, y): Point, (dx, dy): (isize, isize)) -> Point {
+    let wrap =
+        |n: usize, d: isize, size: usize| (n as isize + d).rem_euclid(size as isize) as usize;
+    (wrap(x, dx, width), wrap(y, dy, height))
+}
+
+/// A rectangular grid stored row by row, indexed by `(x, y)` points.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, cells: Vec<T>) -> Self {
+        assert_eq!(cells.len(), width * height);
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    pub fn from_fn<F: FnMut(Point) -> T>(width: usize, height: usize, mut f: F) -> Self {
+        let cells = iproduct!(0..height, 0..width).map(|(y, x)| f((x, y)));
+        Self::new(width, height, cells.collect())
+    }
+
+    /// Parse a character map, one row per line. Panics if the rows aren't
+    /// all the same width.
+    pub fn parse<F: FnMut(char) -> T>(input: &str, mut f: F) -> Self {
+        let lines = input.trim().lines().map(str::trim).collect::<Vec<_>>();
+        let width = lines.first().map_or(0, |line| line.chars().count());
+        for (y, line) in lines.iter().enumerate() {
+            let row_width = line.chars().count();
+            assert_eq!(
+                row_width, width,
+                "row {} is {} wide, not {}",
+                y, row_width, width
+            );
+        }
+        let cells = lines.iter().flat_map(|line| line.chars()).map(&mut f);
+        Self::new(width, lines.len(), cells.collect())
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn get(&self, (x, y): Point) -> Option<&T> {
+        (x < self.width && y < self.height).then(|| &self.cells[x + y * self.width])
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.cells.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        self.cells.iter_mut()
+    }
+
+    /// Every point in the grid, row by row.
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        iproduct!(0..self.height, 0..self.width).map(|(y, x)| (x, y))
+    }
+
+    /// The rows in order, or none at all if the grid has no width.
+    pub fn rows(&self) -> Chunks<'_, T> {
+        // a grid with no width has no cells, so any chunk size gives no rows
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn rows_mut(&mut self) -> ChunksMut<'_, T> {
+        self.cells.chunks_mut(self.width.max(1))
+    }
+
+    pub fn column(&self, x: usize) -> StepBy<Iter<'_, T>> {
+        self.cells[x..].iter().step_by(self.width)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = StepBy<Iter<'_, T>>> {
+        (0..self.width).map(move |x| self.column(x))
+    }
+
+    /// The point `delta` away from `point`, if it's inside the grid.
+    pub fn offset(&self, point: Point, delta: (isize, isize)) -> Option<Point> {
+        offset_within((self.width, self.height), point, delta)
+    }
+
+    /// The point `delta` away from `point`, wrapping around the edges.
+    pub fn wrapping_offset(&self, point: Point, delta: (isize, isize)) -> Point {
+        wrap_within((self.width, self.height), point, delta)
+    }
+
+    fn neighbours<const N: usize>(
+        &self,
+        point: Point,
+        deltas: [(isize, isize); N],
+        wrapping: bool,
+    ) -> impl Iterator<Item = Point> {
+        let size = (self.width, self.height);
+        IntoIterator::into_iter(deltas).filter_map(move |delta| {
+            if wrapping {
+                Some(wrap_within(size, point, delta))
+            } else {
+                offset_within(size, point, delta)
+            }
+        })
+    }
+
+    /// The (up to 4) points orthogonally adjacent to `point`.
+    pub fn neighbours4(&self, point: Point) -> impl Iterator<Item = Point> {
+        self.neighbours(point, ORTHOGONAL, false)
+    }
+
+    /// The (up to 8) points orthogonally or diagonally adjacent to `point`.
+    pub fn neighbours8(&self, point: Point) -> impl Iterator<Item = Point> {
+        self.neighbours(point, SURROUNDING, false)
+    }
+
+    /// The 4 points orthogonally adjacent to `point` on a torus.
+    pub fn wrapping_neighbours4(&self, point: Point) -> impl Iterator<Item = Point> {
+        self.neighbours(point, ORTHOGONAL, true)
+    }
+
+    /// The 8 points orthogonally or diagonally adjacent to `point` on a torus.
+    pub fn wrapping_neighbours8(&self, point: Point) -> impl Iterator<Item = Point> {
+        self.neighbours(point, SURROUNDING, true)
+    }
+
+    pub fn map<U, F: FnMut(&T) -> U>(&self, f: F) -> Grid<U> {
+        Grid::new(self.width, self.height, self.cells.iter().map(f).collect())
+    }
+
+    /// Repeat the grid `across` times horizontally and `down` times vertically,
+    /// mapping each cell along with the `(x, y)` index of the tile it's in.
+    pub fn tile<F: FnMut(&T, Point) -> T>(&self, across: usize, down: usize, mut f: F) -> Self {
+        Grid::from_fn(across * self.width, down * self.height, |(x, y)| {
+            let cell = &self[(x % self.width, y % self.height)];
+            f(cell, (x / self.width, y / self.height))
+        })
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): Point) -> &T {
+        debug_assert!(x < self.width && y < self.height);
+        &self.cells[x + y * self.width]
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, (x, y): Point) -> &mut T {
+        debug_assert!(x < self.width && y < self.height);
+        &mut self.cells[x + y * self.width]
+    }
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_grid {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn test_grid() {
+        let grid = Grid::parse("123\n456\n", |c| c.to_digit(10).unwrap());
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[(2, 1)], 6);
+        assert_eq!(grid.get((3, 0)), None);
+        assert_eq!(grid.column(1).copied().collect::<Vec<_>>(), [2, 5]);
+        assert_eq!(
+            grid.neighbours4((0, 0)).collect::<Vec<_>>(),
+            [(1, 0), (0, 1)]
+        );
+        assert_eq!(grid.neighbours8((1, 0)).count(), 5);
+        assert_eq!(grid.wrapping_neighbours8((1, 0)).count(), 8);
+        assert_eq!(grid.wrapping_offset((0, 0), (-1, -1)), (2, 1));
+        let tiled = grid.tile(2, 1, |&n, (tx, _)| n + 10 * tx as u32);
+        assert_eq!(tiled.to_string(), "123111213\n456141516\n");
+        let expected = indoc! {"
+            #.
+            .#
+        "};
+        let diagonal = Grid::from_fn(2, 2, |(x, y)| if x == y { '#' } else { '.' });
+        assert_eq!(diagonal.to_string(), expected);
+        let empty = Grid::<u8>::new(0, 0, vec![]);
+        assert_eq!(
+            (empty.rows().count(), empty.to_string()),
+            (0, String::new())
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "row 1 is 1 wide, not 2")]
+    fn test_grid_ragged() {
+        // as many cells as a 2x3 grid, but not in its shape
+        Grid::parse("12\n3\n456", |c| c);
+    }
+}
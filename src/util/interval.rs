@@ -0,0 +1,316 @@
+use std::{
+    iter::FromIterator,
+    ops::{Range, RangeInclusive},
+};
+
+/// The half-open interval of integers `start..end`. Closed intervals are
+/// converted on the way in with `Interval::closed`.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Interval {
+    pub start: i64,
+    pub end: i64,
+}
+
+impl Interval {
+    pub const fn new(start: i64, end: i64) -> Self {
+        Self { start, end }
+    }
+
+    /// The interval `first..=last`.
+    pub const fn closed(first: i64, last: i64) -> Self {
+        Self::new(first, last + 1)
+    }
+
+    /// The last integer in the interval, if it isn't empty.
+    pub fn last(self) -> i64 {
+        self.end - 1
+    }
+
+    pub fn len(self) -> i64 {
+        (self.end - self.start).max(0)
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.start >= self.end
+    }
+
+    pub fn contains(self, value: i64) -> bool {
+        self.start <= value && value < self.end
+    }
+
+    pub fn intersection(self, other: Self) -> Option<Self> {
+        let x = Self::new(self.start.max(other.start), self.end.min(other.end));
+        (!x.is_empty()).then_some(x)
+    }
+
+    pub fn iter(self) -> Range<i64> {
+        self.start..self.end
+    }
+}
+
+impl From<Range<i64>> for Interval {
+    fn from(range: Range<i64>) -> Self {
+        Self::new(range.start, range.end)
+    }
+}
+
+impl From<RangeInclusive<i64>> for Interval {
+    fn from(range: RangeInclusive<i64>) -> Self {
+        Self::closed(*range.start(), *range.end())
+    }
+}
+
+/// A set of integers stored as sorted, disjoint and non-adjacent intervals.
+#[derive(Clone, Default, PartialEq, Eq, Hash, Debug)]
+pub struct IntervalSet(Vec<Interval>);
+
+impl IntervalSet {
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    pub fn intervals(&self) -> &[Interval] {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// How many integers are in the set.
+    pub fn measure(&self) -> i64 {
+        self.0.iter().map(|interval| interval.len()).sum()
+    }
+
+    pub fn contains(&self, value: i64) -> bool {
+        let index = self.0.partition_point(|interval| interval.end <= value);
+        self.0
+            .get(index)
+            .is_some_and(|interval| interval.contains(value))
+    }
+
+    pub fn insert(&mut self, interval: Interval) {
+        if interval.is_empty() {
+            return;
+        }
+        // the intervals overlapping or touching the new one get merged into it
+        let lo = self.0.partition_point(|x| x.end < interval.start);
+        let hi = self.0.partition_point(|x| x.start <= interval.end);
+        let merged = self.0[lo..hi].iter().fold(interval, |a, b| {
+            Interval::new(a.start.min(b.start), a.end.max(b.end))
+        });
+        self.0.splice(lo..hi, [merged]);
+    }
+
+    pub fn remove(&mut self, interval: Interval) {
+        if interval.is_empty() {
+            return;
+        }
+        let lo = self.0.partition_point(|x| x.end <= interval.start);
+        let hi = self.0.partition_point(|x| x.start < interval.end);
+        if lo == hi {
+            return;
+        }
+        let left = Interval::new(self.0[lo].start, interval.start);
+        let right = Interval::new(interval.end, self.0[hi - 1].end);
+        let remains = IntoIterator::into_iter([left, right]).filter(|x| !x.is_empty());
+        self.0.splice(lo..hi, remains);
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut union = self.clone();
+        other.0.iter().for_each(|&interval| union.insert(interval));
+        union
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let (mut i, mut j) = (0, 0);
+        let mut intersection = Vec::new();
+        while let (Some(&a), Some(&b)) = (self.0.get(i), other.0.get(j)) {
+            intersection.extend(a.intersection(b));
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        Self(intersection)
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut difference = self.clone();
+        other
+            .0
+            .iter()
+            .for_each(|&interval| difference.remove(interval));
+        difference
+    }
+}
+
+impl From<Interval> for IntervalSet {
+    fn from(interval: Interval) -> Self {
+        let mut set = Self::new();
+        set.insert(interval);
+        set
+    }
+}
+
+impl FromIterator<Interval> for IntervalSet {
+    fn from_iter<I: IntoIterator<Item = Interval>>(iter: I) -> Self {
+        let mut intervals = iter
+            .into_iter()
+            .filter(|x| !x.is_empty())
+            .collect::<Vec<_>>();
+        intervals.sort_unstable();
+        let mut merged: Vec<Interval> = Vec::with_capacity(intervals.len());
+        for interval in intervals {
+            match merged.last_mut() {
+                Some(last) if interval.start <= last.end => last.end = last.end.max(interval.end),
+                _ => merged.push(interval),
+            }
+        }
+        Self(merged)
+    }
+}
+
+/// An axis-aligned box in `N` dimensions, one interval per axis.
+///
+/// A box isn't an `IntervalSet` per axis: the union or difference of two
+/// boxes generally isn't a box, and a product of interval sets would only
+/// defer that problem to each caller. So boxes stay single intervals, which
+/// keeps them `Copy` and `Hash` for day 22's signed counts of overlaps, and
+/// set operations over many boxes go through `union_volume`, which measures
+/// each one-dimensional cross-section as an `IntervalSet`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Cuboid<const N: usize>(pub [Interval; N]);
+
+impl<const N: usize> Cuboid<N> {
+    pub fn volume(&self) -> i64 {
+        self.0.iter().map(|interval| interval.len()).product()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().any(|interval| interval.is_empty())
+    }
+
+    pub fn contains(&self, point: [i64; N]) -> bool {
+        self.0
+            .iter()
+            .zip(point)
+            .all(|(interval, x)| interval.contains(x))
+    }
+
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let mut intersection = *self;
+        for (a, &b) in intersection.0.iter_mut().zip(other.0.iter()) {
+            *a = a.intersection(b)?;
+        }
+        Some(intersection)
+    }
+}
+
+/// The volume covered by any of `cuboids`, sweeping across the first axis
+/// and measuring each slab's cross-section one dimension down.
+pub fn union_volume<const N: usize>(cuboids: &[Cuboid<N>]) -> i64 {
+    fn measure(cuboids: &[&[Interval]]) -> i64 {
+        match cuboids.first().map(|intervals| intervals.len()) {
+            None | Some(0) => 0,
+            Some(1) => cuboids
+                .iter()
+                .map(|intervals| intervals[0])
+                .collect::<IntervalSet>()
+                .measure(),
+            Some(_) => {
+                let mut cuts = cuboids
+                    .iter()
+                    .flat_map(|intervals| [intervals[0].start, intervals[0].end])
+                    .collect::<Vec<_>>();
+                cuts.sort_unstable();
+                cuts.dedup();
+                cuts.windows(2)
+                    .map(|cut| {
+                        let slab = Interval::new(cut[0], cut[1]);
+                        let section = cuboids
+                            .iter()
+                            .filter(|intervals| intervals[0].intersection(slab) == Some(slab))
+                            .map(|intervals| &intervals[1..])
+                            .collect::<Vec<_>>();
+                        slab.len() * measure(&section)
+                    })
+                    .sum()
+            }
+        }
+    }
+
+    let cuboids = cuboids
+        .iter()
+        .filter(|cuboid| !cuboid.is_empty())
+        .map(|cuboid| &cuboid.0[..])
+        .collect::<Vec<_>>();
+    measure(&cuboids)
+}
+
+#[cfg(test)]
+mod test_interval {
+    use super::*;
+    use crate::util::Random;
+
+    #[test]
+    fn test_interval_set_against_points() {
+        let mut random = Random::new(0x9e37_79b9);
+        // every interval starts at most at 39 and is at most 7 long, so lies
+        // in 0..SPAN
+        const SPAN: usize = 39 + 7;
+        let points = |set: &IntervalSet| {
+            (0..SPAN as i64)
+                .filter(|&x| set.contains(x))
+                .collect::<Vec<_>>()
+        };
+        let (mut a, mut b) = (IntervalSet::new(), IntervalSet::new());
+        let (mut a_points, mut b_points) = ([false; SPAN], [false; SPAN]);
+        for step in 0..200 {
+            let start = random.range(0..=39);
+            let interval = Interval::new(start, start + random.range(0..=7));
+            let (set, set_points) = if step % 2 == 0 {
+                (&mut a, &mut a_points)
+            } else {
+                (&mut b, &mut b_points)
+            };
+            let insert = random.below(3) != 0;
+            if insert {
+                set.insert(interval);
+            } else {
+                set.remove(interval);
+            }
+            interval
+                .iter()
+                .for_each(|x| set_points[x as usize] = insert);
+            let expected = (0..SPAN as i64).filter(|&x| set_points[x as usize]);
+            assert_eq!(points(set), expected.collect::<Vec<_>>());
+            assert_eq!(set.measure() as usize, points(set).len());
+            let normalized = set.intervals().iter().copied().collect::<IntervalSet>();
+            assert_eq!(&normalized, set);
+        }
+        let both = |f: fn(bool, bool) -> bool| {
+            (0..SPAN as i64)
+                .filter(|&x| f(a_points[x as usize], b_points[x as usize]))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(points(&a.union(&b)), both(|a, b| a || b));
+        assert_eq!(points(&a.intersection(&b)), both(|a, b| a && b));
+        assert_eq!(points(&a.difference(&b)), both(|a, b| a && !b));
+    }
+
+    #[test]
+    fn test_interval_cuboids() {
+        let square = |x, y, size| Cuboid([Interval::new(x, x + size), Interval::new(y, y + size)]);
+        let (a, b) = (square(0, 0, 4), square(2, 2, 4));
+        assert_eq!(a.intersection(&b), Some(square(2, 2, 2)));
+        assert_eq!(a.intersection(&square(4, 0, 1)), None);
+        assert!(a.contains([3, 0]) && !a.contains([4, 0]));
+        assert_eq!(union_volume(&[a, b, square(1, 1, 2)]), 16 + 16 - 4);
+        let cube = Cuboid([Interval::closed(-1, 1); 3]);
+        assert_eq!(cube.volume(), 27);
+        assert_eq!(union_volume(&[cube, cube]), 27);
+    }
+}
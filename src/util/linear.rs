@@ -0,0 +1,201 @@
+use std::fmt::{self, Display, Formatter};
+
+/// The numbers a `Matrix` can hold. Arithmetic is checked, so that overflow
+/// surfaces as `None` rather than a wrong answer.
+pub trait Scalar: Clone + PartialEq {
+    fn zero() -> Self;
+    fn one() -> Self;
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_mul(self, other: Self) -> Option<Self>;
+}
+
+impl Scalar for u64 {
+    fn zero() -> Self {
+        0
+    }
+
+    fn one() -> Self {
+        1
+    }
+
+    fn checked_add(self, other: Self) -> Option<Self> {
+        u64::checked_add(self, other)
+    }
+
+    fn checked_mul(self, other: Self) -> Option<Self> {
+        u64::checked_mul(self, other)
+    }
+}
+
+/// An integer modulo `M`, which never overflows.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Debug)]
+pub struct Modular<const M: u64>(u64);
+
+impl<const M: u64> Modular<M> {
+    pub fn value(self) -> u64 {
+        self.0
+    }
+}
+
+impl<const M: u64> From<u64> for Modular<M> {
+    fn from(n: u64) -> Self {
+        Self(n % M)
+    }
+}
+
+impl<const M: u64> Scalar for Modular<M> {
+    fn zero() -> Self {
+        Self(0)
+    }
+
+    fn one() -> Self {
+        Self(1 % M)
+    }
+
+    fn checked_add(self, other: Self) -> Option<Self> {
+        Some(Self(
+            ((self.0 as u128 + other.0 as u128) % M as u128) as u64,
+        ))
+    }
+
+    fn checked_mul(self, other: Self) -> Option<Self> {
+        Some(Self((self.0 as u128 * other.0 as u128 % M as u128) as u64))
+    }
+}
+
+impl<const M: u64> Display for Modular<M> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A square matrix, used as a linear map on state vectors of counts.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Matrix<T> {
+    size: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Scalar> Matrix<T> {
+    pub fn zero(size: usize) -> Self {
+        Self {
+            size,
+            cells: vec![T::zero(); size * size],
+        }
+    }
+
+    pub fn identity(size: usize) -> Self {
+        let mut identity = Self::zero(size);
+        (0..size).for_each(|i| identity.cells[i * size + i] = T::one());
+        identity
+    }
+
+    /// The map where each unit in state `from` contributes a unit to state
+    /// `to`, for every `(from, to)` transition (repeats add up).
+    pub fn from_transitions<I: IntoIterator<Item = (usize, usize)>>(
+        size: usize,
+        transitions: I,
+    ) -> Self {
+        let mut matrix = Self::zero(size);
+        for (from, to) in transitions {
+            let cell = &mut matrix.cells[to * size + from];
+            *cell = cell
+                .clone()
+                .checked_add(T::one())
+                .expect("too many transitions");
+        }
+        matrix
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn get(&self, row: usize, column: usize) -> &T {
+        &self.cells[row * self.size + column]
+    }
+
+    pub fn checked_mul(&self, other: &Self) -> Option<Self> {
+        assert_eq!(self.size, other.size);
+        let size = self.size;
+        let mut product = Self::zero(size);
+        for row in 0..size {
+            for k in 0..size {
+                let lhs = self.get(row, k);
+                if *lhs == T::zero() {
+                    continue;
+                }
+                for column in 0..size {
+                    let term = lhs.clone().checked_mul(other.get(k, column).clone())?;
+                    let cell = &mut product.cells[row * size + column];
+                    *cell = cell.clone().checked_add(term)?;
+                }
+            }
+        }
+        Some(product)
+    }
+
+    /// The matrix raised to `exp` by repeated squaring. Overflow in any of
+    /// the intermediate products counts as overflow.
+    pub fn checked_pow(&self, mut exp: u64) -> Option<Self> {
+        let mut result = Self::identity(self.size);
+        let mut square = self.clone();
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = result.checked_mul(&square)?;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                square = square.checked_mul(&square)?;
+            }
+        }
+        Some(result)
+    }
+
+    pub fn checked_apply(&self, vector: &[T]) -> Option<Vec<T>> {
+        assert_eq!(self.size, vector.len());
+        (0..self.size)
+            .map(|row| {
+                vector
+                    .iter()
+                    .enumerate()
+                    .try_fold(T::zero(), |sum, (k, x)| {
+                        sum.checked_add(self.get(row, k).clone().checked_mul(x.clone())?)
+                    })
+            })
+            .collect()
+    }
+}
+
+/// Apply the linear map given by `transitions` to `state` `times` times over,
+/// in O(log times) matrix products.
+pub fn iterate_linear<T: Scalar>(
+    transitions: &Matrix<T>,
+    state: &[T],
+    times: u64,
+) -> Option<Vec<T>> {
+    transitions.checked_pow(times)?.checked_apply(state)
+}
+
+#[cfg(test)]
+mod test_linear {
+    use super::*;
+
+    #[test]
+    fn test_linear_fibonacci() {
+        // (a, b) -> (b, a + b)
+        let fibonacci = Matrix::<u64>::from_transitions(2, [(1, 0), (0, 1), (1, 1)]);
+        assert_eq!(iterate_linear(&fibonacci, &[0, 1], 10), Some(vec![55, 89]));
+        assert_eq!(
+            iterate_linear(&fibonacci, &[0, 1], 92).unwrap()[0],
+            7540113804746346429
+        );
+        assert_eq!(iterate_linear(&fibonacci, &[0, 1], 94), None);
+        let fibonacci =
+            Matrix::<Modular<1_000_000_007>>::from_transitions(2, [(1, 0), (0, 1), (1, 1)]);
+        let state = [0, 1].map(Modular::from);
+        let nth = |n| iterate_linear(&fibonacci, &state, n).unwrap()[0];
+        assert_eq!(nth(92).value(), 7540113804746346429 % 1_000_000_007);
+        assert_eq!(nth(1_000_000_000_000).value(), 730695249);
+    }
+}
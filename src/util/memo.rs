@@ -0,0 +1,157 @@
+use hashbrown::HashMap;
+use std::hash::Hash;
+
+/// How well a `Memo` has been doing.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub struct MemoStats {
+    pub hits: usize,
+    pub misses: usize,
+    pub entries: usize,
+}
+
+/// A cache for a recursive function over hashable keys. The cache outlives
+/// each call, so later calls reuse what earlier ones computed.
+pub struct Memo<K, V> {
+    cache: HashMap<K, V>,
+    hits: usize,
+    misses: usize,
+}
+
+impl<K: Clone + Eq + Hash, V: Clone> Memo<K, V> {
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// A memo with room for `capacity` keys before it has to grow.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            cache: HashMap::with_capacity(capacity),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    pub fn stats(&self) -> MemoStats {
+        MemoStats {
+            hits: self.hits,
+            misses: self.misses,
+            entries: self.cache.len(),
+        }
+    }
+
+    /// The value of `f` at `key`, where `f(key, recurse)` calls `recurse` to
+    /// get the values of subproblems. This recurses on the call stack, so
+    /// use `get_iterative` if the chains of subproblems can get long.
+    pub fn get<F>(&mut self, key: K, f: &F) -> V
+    where
+        F: Fn(K, &mut dyn FnMut(K) -> V) -> V,
+    {
+        if let Some(value) = self.cache.get(&key) {
+            self.hits += 1;
+            return value.clone();
+        }
+        self.misses += 1;
+        let value = f(key.clone(), &mut |key| self.get(key, f));
+        self.cache.insert(key, value.clone());
+        value
+    }
+
+    /// The same as `get`, with the function split in two so that it can be
+    /// evaluated with an explicit stack: `dependencies(key)` lists the
+    /// subproblems of `key`, and `combine(key, values)` computes its value
+    /// from theirs, given in the same order. The subproblems mustn't depend
+    /// on each other cyclically.
+    pub fn get_iterative<D, I, C>(&mut self, key: K, dependencies: D, combine: C) -> V
+    where
+        D: Fn(&K) -> I,
+        I: IntoIterator<Item = K>,
+        C: Fn(&K, Vec<(K, V)>) -> V,
+    {
+        if let Some(value) = self.cache.get(&key) {
+            self.hits += 1;
+            return value.clone();
+        }
+        self.misses += 1;
+        let deps = dependencies(&key).into_iter().collect::<Vec<_>>();
+        // each frame is a key, its dependencies, and how many are resolved
+        let mut stack = vec![(key.clone(), deps, 0)];
+        while let Some((_, deps, resolved)) = stack.last_mut() {
+            if let Some(dep) = deps.get(*resolved) {
+                *resolved += 1;
+                if self.cache.contains_key(dep) {
+                    self.hits += 1;
+                } else {
+                    self.misses += 1;
+                    let dep = dep.clone();
+                    let dep_deps = dependencies(&dep).into_iter().collect();
+                    stack.push((dep, dep_deps, 0));
+                }
+            } else {
+                let (key, deps, _) = stack.pop().unwrap();
+                let values = deps
+                    .into_iter()
+                    .map(|dep| {
+                        let value = self.cache[&dep].clone();
+                        (dep, value)
+                    })
+                    .collect();
+                let value = combine(&key, values);
+                self.cache.insert(key, value);
+            }
+        }
+        self.cache[&key].clone()
+    }
+}
+
+impl<K: Clone + Eq + Hash, V: Clone> Default for Memo<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test_memo {
+    use super::*;
+
+    #[test]
+    fn test_memo_modes() {
+        // the number of lattice paths from (0, 0) to (x, y)
+        let paths = |(x, y): (u32, u32), recurse: &mut dyn FnMut((u32, u32)) -> u64| match (x, y) {
+            (0, _) | (_, 0) => 1,
+            _ => recurse((x - 1, y)) + recurse((x, y - 1)),
+        };
+        let mut memo = Memo::with_capacity(64);
+        assert_eq!(memo.get((16, 16), &paths), 601080390);
+        let stats = memo.stats();
+        assert_eq!(
+            (stats.misses, stats.entries),
+            (16 * 16 + 2 * 16, stats.misses)
+        );
+        assert_eq!(memo.get((16, 15), &paths), 300540195);
+        assert_eq!(memo.stats().hits, stats.hits + 1);
+
+        // deep enough to overflow the stack if done recursively
+        let mut memo = Memo::new();
+        let depth = 200_000u64;
+        let triangle = memo.get_iterative(
+            depth,
+            |&n| n.checked_sub(1),
+            |&n, values| n + values.first().map_or(0, |&(_, value)| value),
+        );
+        assert_eq!(triangle, depth * (depth + 1) / 2);
+        assert_eq!(memo.stats().entries, depth as usize + 1);
+
+        let mut memo = Memo::new();
+        let dependencies = |&(x, y): &(u32, u32)| match (x, y) {
+            (0, _) | (_, 0) => vec![],
+            _ => vec![(x - 1, y), (x, y - 1)],
+        };
+        let combine =
+            |_: &_, values: Vec<(_, u64)>| values.iter().map(|&(_, v)| v).sum::<u64>().max(1);
+        assert_eq!(
+            memo.get_iterative((16, 16), dependencies, combine),
+            601080390
+        );
+        assert_eq!(memo.stats().misses, stats.misses);
+    }
+}
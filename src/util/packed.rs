@@ -0,0 +1,121 @@
+/// Declare a newtype over an unsigned integer holding named fixed-width
+/// fields, packed from the least significant bit up. A field is either a
+/// single value, `name: u8 = 3`, or an array of them, `names: [u8; 4] = 3`,
+/// where the number after `=` is the width in bits of each value. This
+/// generates getters named after the fields and setters prefixed `set_`,
+/// which panic on an index out of range or a value too wide for its field.
+///
+/// The type derives `Ord` and `Hash` (comparing the raw integer), so it can
+/// be used as a search state, and the raw integer is the tuple field `.0`.
+#[macro_export]
+macro_rules! packed {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident($repr:ty) {
+            $($fields:tt)*
+        }
+    ) => {
+        $(#[$attr])*
+        #[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+        $vis struct $name($repr);
+
+        $crate::packed!(@fields $vis $name $repr; 0; $($fields)*);
+    };
+    (@fields $vis:vis $name:ident $repr:ty; $offset:expr; $(,)?) => {
+        const _: () = assert!($offset <= <$repr>::BITS as usize, "fields don't fit");
+    };
+    (
+        @fields $vis:vis $name:ident $repr:ty; $offset:expr;
+        $field:ident: [$ty:ty; $count:literal] = $bits:literal $(, $($rest:tt)*)?
+    ) => {
+        paste::paste! {
+            #[allow(dead_code)]
+            impl $name {
+                $vis fn $field(self, index: usize) -> $ty {
+                    assert!(index < $count, "{} index {} out of range", stringify!($field), index);
+                    let mask = <$repr>::MAX >> (<$repr>::BITS as usize - $bits);
+                    (self.0 >> ($offset + index * $bits) & mask) as $ty
+                }
+
+                $vis fn [<set_ $field>](&mut self, index: usize, value: $ty) {
+                    assert!(index < $count, "{} index {} out of range", stringify!($field), index);
+                    assert!(
+                        (value as u128) >> $bits == 0,
+                        "{} doesn't fit in {} bits", stringify!($field), $bits
+                    );
+                    let shift = $offset + index * $bits;
+                    let mask = <$repr>::MAX >> (<$repr>::BITS as usize - $bits);
+                    self.0 = self.0 & !(mask << shift) | (value as $repr) << shift;
+                }
+            }
+        }
+        $crate::packed!(@fields $vis $name $repr; $offset + $count * $bits; $($($rest)*)?);
+    };
+    (
+        @fields $vis:vis $name:ident $repr:ty; $offset:expr;
+        $field:ident: $ty:ty = $bits:literal $(, $($rest:tt)*)?
+    ) => {
+        paste::paste! {
+            #[allow(dead_code)]
+            impl $name {
+                $vis fn $field(self) -> $ty {
+                    let mask = <$repr>::MAX >> (<$repr>::BITS as usize - $bits);
+                    (self.0 >> $offset & mask) as $ty
+                }
+
+                $vis fn [<set_ $field>](&mut self, value: $ty) {
+                    assert!(
+                        (value as u128) >> $bits == 0,
+                        "{} doesn't fit in {} bits", stringify!($field), $bits
+                    );
+                    let mask = <$repr>::MAX >> (<$repr>::BITS as usize - $bits);
+                    self.0 = self.0 & !(mask << $offset) | (value as $repr) << $offset;
+                }
+            }
+        }
+        $crate::packed!(@fields $vis $name $repr; $offset + $bits; $($($rest)*)?);
+    };
+}
+
+#[cfg(test)]
+mod test_packed {
+    crate::packed! {
+        struct Example(u32) {
+            flag: u8 = 1,
+            pairs: [u8; 3] = 5,
+            wide: u16 = 16,
+        }
+    }
+
+    #[test]
+    fn test_packed_fields() {
+        let mut example = Example::default();
+        example.set_flag(1);
+        example.set_pairs(1, 31);
+        example.set_wide(0xbeef);
+        assert_eq!(example.0, 1 | 31 << 6 | 0xbeef << 16);
+        example.set_pairs(1, 2);
+        example.set_pairs(2, 7);
+        let fields = (
+            example.flag(),
+            [0, 1, 2].map(|i| example.pairs(i)),
+            example.wide(),
+        );
+        assert_eq!(fields, (1, [0, 2, 7], 0xbeef));
+        let mut smaller = example;
+        smaller.set_wide(0xbeee);
+        assert!(smaller < example);
+    }
+
+    #[test]
+    #[should_panic(expected = "pairs doesn't fit in 5 bits")]
+    fn test_packed_overflow() {
+        Example::default().set_pairs(0, 32);
+    }
+
+    #[test]
+    #[should_panic(expected = "pairs index 3 out of range")]
+    fn test_packed_index() {
+        Example::default().pairs(3);
+    }
+}
@@ -0,0 +1,372 @@
+use super::Grid;
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+/// Where parsing failed and what was expected there. Lines and columns count
+/// from 1, like an editor's.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub expected: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: expected {}",
+            self.line, self.column, self.expected
+        )
+    }
+}
+
+impl Error for ParseError {}
+
+pub type ParseResult<T> = Result<T, ParseError>;
+
+/// Parse all of `input` with `parser`, allowing only whitespace after it.
+pub fn parse<'a, T, F>(input: &'a str, parser: F) -> ParseResult<T>
+where
+    F: FnOnce(&mut Cursor<'a>) -> ParseResult<T>,
+{
+    let mut cursor = Cursor::new(input);
+    let value = parser(&mut cursor)?;
+    cursor.take_while(char::is_whitespace);
+    cursor.end()?;
+    Ok(value)
+}
+
+/// A position in some input text. The combinators consume from it and report
+/// failures at the position they got to.
+#[derive(Clone, Copy, Debug)]
+pub struct Cursor<'a> {
+    input: &'a str,
+    offset: usize,
+}
+
+impl<'a> Cursor<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self { input, offset: 0 }
+    }
+
+    /// The input not yet consumed.
+    pub fn rest(&self) -> &'a str {
+        &self.input[self.offset..]
+    }
+
+    pub fn is_at_end(&self) -> bool {
+        self.rest().is_empty()
+    }
+
+    fn position(&self) -> (usize, usize) {
+        let before = &self.input[..self.offset];
+        let line = before.matches('\n').count() + 1;
+        let column = before.rsplit('\n').next().unwrap().chars().count() + 1;
+        (line, column)
+    }
+
+    pub fn error<E: Display>(&self, expected: E) -> ParseError {
+        let (line, column) = self.position();
+        ParseError {
+            line,
+            column,
+            expected: expected.to_string(),
+        }
+    }
+
+    pub fn end(&self) -> ParseResult<()> {
+        match self.is_at_end() {
+            true => Ok(()),
+            false => Err(self.error("end of input")),
+        }
+    }
+
+    /// Consume `literal` if the input continues with it.
+    pub fn eat(&mut self, literal: &str) -> bool {
+        let found = self.rest().starts_with(literal);
+        if found {
+            self.offset += literal.len();
+        }
+        found
+    }
+
+    pub fn literal(&mut self, literal: &str) -> ParseResult<()> {
+        match self.eat(literal) {
+            true => Ok(()),
+            false => Err(self.error(format_args!("{:?}", literal))),
+        }
+    }
+
+    /// Consume characters for as long as `pred` holds, possibly none.
+    pub fn take_while<P: FnMut(char) -> bool>(&mut self, mut pred: P) -> &'a str {
+        let rest = self.rest();
+        let len = rest.find(|c| !pred(c)).unwrap_or(rest.len());
+        self.offset += len;
+        &rest[..len]
+    }
+
+    /// One of the characters in `chars`.
+    pub fn one_of(&mut self, chars: &str) -> ParseResult<char> {
+        match self.rest().chars().next().filter(|&c| chars.contains(c)) {
+            Some(c) => {
+                self.offset += c.len_utf8();
+                Ok(c)
+            }
+            None => Err(self.error(format_args!("one of {:?}", chars))),
+        }
+    }
+
+    /// A run of ASCII letters and digits.
+    pub fn word(&mut self) -> ParseResult<&'a str> {
+        match self.take_while(|c| c.is_ascii_alphanumeric()) {
+            "" => Err(self.error("a word")),
+            word => Ok(word),
+        }
+    }
+
+    /// A decimal integer with an optional sign, which must fit in a `T`.
+    pub fn int<T: FromStr>(&mut self) -> ParseResult<T> {
+        let start = *self;
+        let _ = self.eat("-") || self.eat("+");
+        if self.take_while(|c| c.is_ascii_digit()).is_empty() {
+            *self = start;
+            return Err(self.error("an integer"));
+        }
+        let digits = &start.rest()[..self.offset - start.offset];
+        digits.parse().map_err(|_| {
+            start.error(format_args!(
+                "an integer that fits in a {}",
+                std::any::type_name::<T>()
+            ))
+        })
+    }
+
+    /// An inclusive range `first..last`, returned as `(first, last)`.
+    pub fn range<T: FromStr>(&mut self) -> ParseResult<(T, T)> {
+        let first = self.int()?;
+        self.literal("..")?;
+        Ok((first, self.int()?))
+    }
+
+    /// A field `key=value`, with the value parsed by `value`.
+    pub fn field<T, F>(&mut self, key: &str, value: F) -> ParseResult<T>
+    where
+        F: FnOnce(&mut Self) -> ParseResult<T>,
+    {
+        self.literal(key)?;
+        self.literal("=")?;
+        value(self)
+    }
+
+    /// One or more `item`s with `separator` between them.
+    pub fn separated<T, F>(&mut self, separator: &str, mut item: F) -> ParseResult<Vec<T>>
+    where
+        F: FnMut(&mut Self) -> ParseResult<T>,
+    {
+        let mut items = vec![item(self)?];
+        while self.eat(separator) {
+            items.push(item(self)?);
+        }
+        Ok(items)
+    }
+
+    /// One `item` per line, up to a blank line or the end of the input. Each
+    /// item has to take up its whole line, and the line breaks are consumed.
+    pub fn lines<T, F>(&mut self, mut item: F) -> ParseResult<Vec<T>>
+    where
+        F: FnMut(&mut Self) -> ParseResult<T>,
+    {
+        let mut items = Vec::new();
+        while !self.is_at_end() && !self.rest().starts_with('\n') {
+            items.push(item(self)?);
+            if !self.eat("\n") && !self.is_at_end() {
+                return Err(self.error("the end of the line"));
+            }
+        }
+        Ok(items)
+    }
+
+    /// One or more `section`s separated by blank lines.
+    pub fn sections<T, F>(&mut self, mut section: F) -> ParseResult<Vec<T>>
+    where
+        F: FnMut(&mut Self) -> ParseResult<T>,
+    {
+        let mut sections = vec![section(self)?];
+        while self.eat("\n") && !self.is_at_end() {
+            sections.push(section(self)?);
+        }
+        Ok(sections)
+    }
+
+    /// A rectangle of characters, one row per line, up to a blank line or the
+    /// end of the input. `cell` rejects a character by returning `None`.
+    pub fn grid<T, F>(&mut self, mut cell: F) -> ParseResult<Grid<T>>
+    where
+        F: FnMut(char) -> Option<T>,
+    {
+        let mut width = None;
+        let mut cells = Vec::new();
+        let rows = self.lines(|cursor| {
+            let mut row_width = 0;
+            while let Some(c) = cursor.rest().chars().next().filter(|&c| c != '\n') {
+                cells.push(cell(c).ok_or_else(|| cursor.error("a grid cell"))?);
+                cursor.offset += c.len_utf8();
+                row_width += 1;
+            }
+            match *width.get_or_insert(row_width) {
+                width if width == row_width => Ok(()),
+                width => Err(cursor.error(format_args!("a row {} cells wide", width))),
+            }
+        })?;
+        Ok(Grid::new(width.unwrap_or(0), rows.len(), cells))
+    }
+
+    /// A run of hex digits, read as a stream of bits.
+    pub fn hex_bits(&mut self) -> ParseResult<Bits> {
+        let (line, column) = self.position();
+        let digits = self.take_while(|c| c.is_ascii_hexdigit());
+        if digits.is_empty() {
+            return Err(self.error("a hex digit"));
+        }
+        Ok(Bits {
+            digits: digits
+                .chars()
+                .map(|c| c.to_digit(16).unwrap() as u8)
+                .collect(),
+            position: 0,
+            line,
+            column,
+        })
+    }
+}
+
+/// The bits of some hex digits, read most significant first. Running out of
+/// bits is reported just past the last digit.
+#[derive(Clone, Debug)]
+pub struct Bits {
+    digits: Vec<u8>,
+    position: usize,
+    line: usize,
+    column: usize,
+}
+
+impl Bits {
+    /// How many bits have been read.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// An error at the digit holding the next bit.
+    pub fn error<E: Display>(&self, expected: E) -> ParseError {
+        ParseError {
+            line: self.line,
+            column: self.column + self.position / 4,
+            expected: expected.to_string(),
+        }
+    }
+
+    /// The next `n` bits as a number, for `n` up to 64.
+    pub fn take(&mut self, n: usize) -> ParseResult<u64> {
+        assert!(n <= 64);
+        if self.position + n > 4 * self.digits.len() {
+            return Err(ParseError {
+                line: self.line,
+                column: self.column + self.digits.len(),
+                expected: format!("{} more bits", self.position + n - 4 * self.digits.len()),
+            });
+        }
+        let value = (self.position..self.position + n).fold(0, |value, i| {
+            let bit = self.digits[i / 4] >> (3 - i % 4) & 1;
+            value << 1 | bit as u64
+        });
+        self.position += n;
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod test_parse {
+    use super::*;
+
+    #[test]
+    fn test_parse_combinators() {
+        let target = parse("target area: x=20..30, y=-10..-5\n", |cursor| {
+            cursor.literal("target area: ")?;
+            let x = cursor.field("x", Cursor::range::<i32>)?;
+            cursor.literal(", ")?;
+            Ok((x, cursor.field("y", Cursor::range::<i32>)?))
+        });
+        assert_eq!(target, Ok(((20, 30), (-10, -5))));
+
+        let sections = parse("1,2\n3,4\n\n5\n", |cursor| {
+            cursor.sections(|cursor| cursor.lines(|cursor| cursor.separated(",", Cursor::int)))
+        });
+        let expected: Vec<Vec<Vec<u8>>> = vec![vec![vec![1, 2], vec![3, 4]], vec![vec![5]]];
+        assert_eq!(sections, Ok(expected));
+
+        let grid = parse("#.\n.#", |cursor| {
+            cursor.grid(|c| match c {
+                '#' => Some(true),
+                '.' => Some(false),
+                _ => None,
+            })
+        })
+        .unwrap();
+        assert_eq!((grid.width(), grid.height()), (2, 2));
+        assert!(grid[(1, 1)] && !grid[(1, 0)]);
+
+        let mut cursor = Cursor::new("AB#");
+        assert_eq!(cursor.one_of("AB"), Ok('A'));
+        assert_eq!(cursor.one_of("AB"), Ok('B'));
+        assert!(cursor.one_of("AB").is_err());
+
+        let mut bits = Cursor::new("D2FE28").hex_bits().unwrap();
+        assert_eq!([3, 3, 5].map(|n| bits.take(n).unwrap()), [6, 4, 23]);
+        assert_eq!(bits.position(), 11);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |input, expected: &str| {
+            let result = parse(input, |cursor| {
+                cursor.lines(|cursor| {
+                    let name = cursor.word()?;
+                    cursor.literal(": ")?;
+                    Ok((name, cursor.separated(", ", Cursor::int::<u8>)?))
+                })
+            });
+            assert_eq!(result.unwrap_err().to_string(), expected);
+        };
+        error(
+            "a: 1, 2\nb: 3,4",
+            "line 2, column 5: expected the end of the line",
+        );
+        error(
+            "a: 1\nb: 256",
+            "line 2, column 4: expected an integer that fits in a u8",
+        );
+        error("a: 1\n\nb: x", "line 3, column 1: expected end of input");
+        error("a: -", "line 1, column 4: expected an integer");
+        error("a 1", "line 1, column 2: expected \": \"");
+        let amphipods = parse("AE", |cursor| {
+            cursor.one_of("ABCD")?;
+            cursor.one_of("ABCD")
+        });
+        assert_eq!(
+            amphipods.unwrap_err().to_string(),
+            "line 1, column 2: expected one of \"ABCD\""
+        );
+
+        let ragged = parse("##\n#", |cursor| cursor.grid(Some));
+        assert_eq!(
+            ragged.unwrap_err().to_string(),
+            "line 2, column 2: expected a row 2 cells wide"
+        );
+        let err = Cursor::new("8").hex_bits().unwrap().take(5).unwrap_err();
+        assert_eq!(err.to_string(), "line 1, column 2: expected 1 more bits");
+    }
+}
@@ -0,0 +1,153 @@
+/// The digits of an integer in some radix, most significant first, or least
+/// significant first when reversed.
+#[derive(Clone, Debug)]
+pub struct Digits {
+    // the digits not yet taken, with `len` of them, leading zeros included
+    value: u64,
+    radix: u64,
+    len: u32,
+}
+
+impl Iterator for Digits {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        self.len = self.len.checked_sub(1)?;
+        // a place value too big for a u64 can only hold a leading zero
+        match self.radix.checked_pow(self.len) {
+            Some(place) => {
+                let digit = self.value / place;
+                self.value %= place;
+                Some(digit)
+            }
+            None => Some(0),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len as usize, Some(self.len as usize))
+    }
+}
+
+impl DoubleEndedIterator for Digits {
+    fn next_back(&mut self) -> Option<u64> {
+        self.len = self.len.checked_sub(1)?;
+        let digit = self.value % self.radix;
+        self.value /= self.radix;
+        Some(digit)
+    }
+}
+
+impl ExactSizeIterator for Digits {}
+
+/// The digits of `n` in base `radix`, without leading zeros, except that
+/// zero is a single digit 0.
+pub fn digits(n: u64, radix: u64) -> Digits {
+    assert!(radix >= 2, "radix {} is less than 2", radix);
+    let mut len = 1;
+    let mut rest = n / radix;
+    while rest > 0 {
+        len += 1;
+        rest /= radix;
+    }
+    Digits {
+        value: n,
+        radix,
+        len,
+    }
+}
+
+/// The digits of `n` in base `radix`, padded with leading zeros to exactly
+/// `width` of them, or `None` if it needs more.
+pub fn digits_padded(n: u64, radix: u64, width: u32) -> Option<Digits> {
+    let digits = digits(n, radix);
+    (digits.len <= width || n == 0).then_some(Digits {
+        len: width,
+        ..digits
+    })
+}
+
+/// The integer with the given digits in base `radix`, most significant
+/// first, or `None` if it overflows. Panics on a digit that isn't below
+/// `radix`.
+pub fn from_digits(digits: impl IntoIterator<Item = u64>, radix: u64) -> Option<u64> {
+    digits.into_iter().try_fold(0u64, |n, digit| {
+        assert!(
+            digit < radix,
+            "digit {} out of range for radix {}",
+            digit,
+            radix
+        );
+        n.checked_mul(radix)?.checked_add(digit)
+    })
+}
+
+/// `from_digits` with the least significant digit first.
+pub fn from_digits_rev(digits: impl IntoIterator<Item = u64>, radix: u64) -> Option<u64> {
+    // the place value overflows before the number does if it ends in zeros
+    let mut place = Some(1u64);
+    digits.into_iter().try_fold(0u64, |n, digit| {
+        assert!(
+            digit < radix,
+            "digit {} out of range for radix {}",
+            digit,
+            radix
+        );
+        let value = match digit {
+            0 => 0,
+            _ => place?.checked_mul(digit)?,
+        };
+        place = place.and_then(|place| place.checked_mul(radix));
+        n.checked_add(value)
+    })
+}
+
+#[cfg(test)]
+mod test_radix {
+    use super::*;
+    use crate::util::Random;
+
+    #[test]
+    fn test_radix_round_trips() {
+        assert_eq!(digits(0, 10).collect::<Vec<_>>(), [0]);
+        assert_eq!(digits(1234, 10).collect::<Vec<_>>(), [1, 2, 3, 4]);
+        assert_eq!(digits(1234, 10).rev().collect::<Vec<_>>(), [4, 3, 2, 1]);
+        assert_eq!(digits(0b1011, 2).len(), 4);
+        let padded = digits_padded(5, 2, 6).unwrap();
+        assert_eq!(padded.clone().collect::<Vec<_>>(), [0, 0, 0, 1, 0, 1]);
+        assert_eq!(padded.rev().collect::<Vec<_>>(), [1, 0, 1, 0, 0, 0]);
+        assert!(digits_padded(64, 2, 6).is_none());
+        assert_eq!(digits_padded(0, 7, 0).unwrap().len(), 0);
+        let wide = digits_padded(u64::MAX, 10, 30).unwrap().collect::<Vec<_>>();
+        assert_eq!(from_digits(wide[10..].iter().copied(), 10), Some(u64::MAX));
+
+        let mut random = Random::new(0x9e37_79b9_7f4a_7c15);
+        for _ in 0..1000 {
+            let n = random.next_u64() >> random.below(64);
+            let radix = 2 + random.below(40) as u64;
+            assert_eq!(from_digits(digits(n, radix), radix), Some(n));
+            assert_eq!(from_digits_rev(digits(n, radix).rev(), radix), Some(n));
+            let mut both_ends = digits(n, radix);
+            let (first, last) = (both_ends.next(), both_ends.next_back());
+            assert_eq!(first.unwrap() > 0, n > 0);
+            assert_eq!(last, (n >= radix).then(|| n % radix));
+        }
+        assert_eq!(from_digits(digits(u64::MAX, 3), 3), Some(u64::MAX));
+    }
+
+    #[test]
+    fn test_radix_overflow() {
+        let max = digits(u64::MAX, 10).collect::<Vec<_>>();
+        assert_eq!(from_digits(max.iter().copied(), 10), Some(u64::MAX));
+        assert_eq!(from_digits(max.iter().copied().chain([0]), 10), None);
+        let mut one_more = max.clone();
+        *one_more.last_mut().unwrap() += 1;
+        assert_eq!(from_digits(one_more, 10), None);
+        // leading zeros don't count, however many there are
+        let zeros = std::iter::repeat_n(0, 100);
+        assert_eq!(from_digits(zeros.clone().chain([1]), 2), Some(1));
+        assert_eq!(from_digits_rev(std::iter::once(1).chain(zeros), 2), Some(1));
+        assert_eq!(from_digits_rev((0..64).map(|_| 1), 2), Some(u64::MAX));
+        assert_eq!(from_digits_rev((0..65).map(|_| 1), 2), None);
+    }
+}
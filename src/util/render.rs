@@ -0,0 +1,225 @@
+use super::Grid;
+use std::{
+    fmt::Write as _,
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+/// A colour, as red, green and blue.
+pub type Rgb = [u8; 3];
+
+/// A picture, one pixel per cell, in shades of grey or in colour.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Frame {
+    Gray(Grid<u8>),
+    Color(Grid<Rgb>),
+}
+
+impl Frame {
+    /// Each cell of `grid` as a shade of grey, from black at 0.
+    pub fn gray<T, F: FnMut(&T) -> u8>(grid: &Grid<T>, shade: F) -> Self {
+        Self::Gray(grid.map(shade))
+    }
+
+    pub fn color<T, F: FnMut(&T) -> Rgb>(grid: &Grid<T>, paint: F) -> Self {
+        Self::Color(grid.map(paint))
+    }
+
+    pub fn width(&self) -> usize {
+        match self {
+            Self::Gray(grid) => grid.width(),
+            Self::Color(grid) => grid.width(),
+        }
+    }
+
+    pub fn height(&self) -> usize {
+        match self {
+            Self::Gray(grid) => grid.height(),
+            Self::Color(grid) => grid.height(),
+        }
+    }
+
+    fn rgb(&self, point: (usize, usize)) -> Rgb {
+        match self {
+            Self::Gray(grid) => [grid[point]; 3],
+            Self::Color(grid) => grid[point],
+        }
+    }
+
+    /// The extension of the file `to_netpbm` makes.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Gray(_) => "pgm",
+            Self::Color(_) => "ppm",
+        }
+    }
+
+    /// The frame as a binary PGM image if it's grey, or PPM if in colour.
+    pub fn to_netpbm(&self) -> Vec<u8> {
+        let (magic, pixels) = match self {
+            Self::Gray(grid) => ("P5", grid.iter().copied().collect::<Vec<_>>()),
+            Self::Color(grid) => ("P6", grid.iter().flatten().copied().collect()),
+        };
+        let header = format!("{}\n{} {}\n255\n", magic, self.width(), self.height());
+        [header.into_bytes(), pixels].concat()
+    }
+
+    /// The frame as text for a terminal with 24-bit colour, two pixels to a
+    /// character: the top one in the foreground of a half block, the bottom
+    /// one behind it.
+    pub fn to_ansi(&self) -> String {
+        let mut text = String::new();
+        for y in (0..self.height()).step_by(2) {
+            for x in 0..self.width() {
+                let [r, g, b] = self.rgb((x, y));
+                write!(text, "\x1b[38;2;{};{};{}m", r, g, b).unwrap();
+                if y + 1 < self.height() {
+                    let [r, g, b] = self.rgb((x, y + 1));
+                    write!(text, "\x1b[48;2;{};{};{}m", r, g, b).unwrap();
+                }
+                text.push('▀');
+            }
+            text.push_str("\x1b[0m\n");
+        }
+        text
+    }
+}
+
+/// The `n`th of a sequence of colours that are easy to tell apart, for
+/// labelling regions.
+pub fn palette(n: usize) -> Rgb {
+    // stepping round the hues by the golden angle keeps neighbours apart
+    let hue = (n as f64 * 0.618_033_988_75).fract() * 6.0;
+    let fall = (255.0 * (1.0 - hue.fract())) as u8;
+    let rise = (255.0 * hue.fract()) as u8;
+    match hue as u8 {
+        0 => [255, rise, 0],
+        1 => [fall, 255, 0],
+        2 => [0, 255, rise],
+        3 => [0, fall, 255],
+        4 => [rise, 0, 255],
+        _ => [255, 0, fall],
+    }
+}
+
+enum Target {
+    Terminal,
+    Images(PathBuf),
+    Memory(Vec<Frame>),
+}
+
+/// Where a day's frames go: to the terminal, into a directory as an image
+/// sequence numbered from `frame0000`, or into memory to look at later.
+pub struct Renderer {
+    target: Target,
+    frames: usize,
+}
+
+impl Renderer {
+    pub fn terminal() -> Self {
+        Self {
+            target: Target::Terminal,
+            frames: 0,
+        }
+    }
+
+    /// Write frames to `dir`, which is created if it doesn't exist.
+    pub fn images<P: Into<PathBuf>>(dir: P) -> Self {
+        Self {
+            target: Target::Images(dir.into()),
+            frames: 0,
+        }
+    }
+
+    /// Keep the frames, for `drawn` to give back.
+    pub fn memory() -> Self {
+        Self {
+            target: Target::Memory(Vec::new()),
+            frames: 0,
+        }
+    }
+
+    /// The frames kept by a renderer into memory, or none for any other.
+    pub fn drawn(&self) -> &[Frame] {
+        match &self.target {
+            Target::Memory(frames) => frames,
+            _ => &[],
+        }
+    }
+
+    /// How many frames have been drawn.
+    pub fn frames(&self) -> usize {
+        self.frames
+    }
+
+    pub fn draw(&mut self, frame: &Frame) -> io::Result<()> {
+        match &mut self.target {
+            Target::Terminal => {
+                let mut stdout = io::stdout().lock();
+                writeln!(stdout, "frame {}:", self.frames)?;
+                stdout.write_all(frame.to_ansi().as_bytes())?;
+            }
+            Target::Images(dir) => {
+                fs::create_dir_all(&dir)?;
+                let name = format!("frame{:04}.{}", self.frames, frame.extension());
+                fs::write(dir.join(name), frame.to_netpbm())?;
+            }
+            Target::Memory(frames) => frames.push(frame.clone()),
+        }
+        self.frames += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_render {
+    use super::*;
+
+    #[test]
+    fn test_render_formats() {
+        let grid = Grid::new(2, 3, vec![0, 1, 2, 3, 4, 5]);
+        let gray = Frame::gray(&grid, |&n| n as u8 * 50);
+        assert_eq!(gray.to_netpbm(), b"P5\n2 3\n255\n\x00\x32\x64\x96\xc8\xfa");
+        let color = Frame::color(&grid, |&n| [n as u8, 0, 7]);
+        let ppm = color.to_netpbm();
+        assert_eq!(&ppm[..11], b"P6\n2 3\n255\n");
+        assert_eq!(&ppm[11..17], [0, 0, 7, 1, 0, 7]);
+        assert_eq!(ppm.len(), 11 + 3 * 6);
+
+        // two rows to a line, with the odd one out lacking a background
+        let ansi = gray.to_ansi();
+        let lines = ansi.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("\x1b[38;2;0;0;0m\x1b[48;2;100;100;100m▀"));
+        assert_eq!(
+            lines[1],
+            "\x1b[38;2;200;200;200m▀\x1b[38;2;250;250;250m▀\x1b[0m"
+        );
+
+        let colors = (0..12).map(palette).collect::<Vec<_>>();
+        assert!(colors
+            .iter()
+            .enumerate()
+            .all(|(i, c)| !colors[..i].contains(c)));
+    }
+
+    #[test]
+    fn test_render_images() {
+        let dir = std::env::temp_dir().join(format!("aoc2021-render-{}", std::process::id()));
+        let mut renderer = Renderer::images(&dir);
+        let frame = Frame::gray(&Grid::new(1, 1, vec![9]), |&n| n);
+        renderer.draw(&frame).unwrap();
+        renderer.draw(&frame).unwrap();
+        assert_eq!(renderer.frames(), 2);
+        assert_eq!(
+            fs::read(dir.join("frame0001.pgm")).unwrap(),
+            frame.to_netpbm()
+        );
+        fs::remove_dir_all(dir).unwrap();
+
+        let mut renderer = Renderer::memory();
+        renderer.draw(&frame).unwrap();
+        assert_eq!(renderer.drawn(), [frame]);
+    }
+}
@@ -0,0 +1,341 @@
+use hashbrown::{hash_map::Entry, HashMap, HashSet};
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, VecDeque},
+    hash::Hash,
+    ops::Add,
+};
+
+pub fn bfs<N, F, G, I>(root: N, adjacent_gen: F, mut visitor: G) -> HashSet<N>
+where
+    N: Copy + Eq + Hash,
+    F: Fn(N) -> I,
+    I: Iterator<Item = N>,
+    G: FnMut(N),
+{
+    let mut queue = VecDeque::new();
+    let mut visited = HashSet::new();
+    queue.push_front(root);
+    visited.insert(root);
+    while let Some(node) = queue.pop_back() {
+        for x in adjacent_gen(node) {
+            if !visited.contains(&x) {
+                visitor(x);
+                queue.push_front(x);
+                visited.insert(x);
+            }
+        }
+    }
+    visited
+}
+
+/// The nodes reached by `bfs_tree`, with how far each is from the nearest
+/// root and which node it was first reached from.
+pub struct BfsTree<N> {
+    reached: HashMap<N, (usize, Option<N>)>,
+    goal: Option<N>,
+}
+
+impl<N: Copy + Eq + Hash> BfsTree<N> {
+    pub fn len(&self) -> usize {
+        self.reached.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.reached.is_empty()
+    }
+
+    pub fn contains(&self, node: N) -> bool {
+        self.reached.contains_key(&node)
+    }
+
+    pub fn depth(&self, node: N) -> Option<usize> {
+        self.reached.get(&node).map(|&(depth, _)| depth)
+    }
+
+    pub fn parent(&self, node: N) -> Option<N> {
+        self.reached.get(&node).and_then(|&(_, parent)| parent)
+    }
+
+    /// The first node found that satisfied the goal predicate.
+    pub fn goal(&self) -> Option<N> {
+        self.goal
+    }
+
+    /// A shortest path from one of the roots to `node`, inclusive.
+    pub fn path_to(&self, node: N) -> Option<Vec<N>> {
+        let mut path = vec![node];
+        self.reached.get(&node)?;
+        while let Some(parent) = self.parent(*path.last().unwrap()) {
+            path.push(parent);
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = N> + '_ {
+        self.reached.keys().copied()
+    }
+}
+
+/// Breadth-first search out from all of `roots` at once, stopping early at
+/// the first node satisfying `goal_pred` (pass `|_| false` to search it all).
+pub fn bfs_tree<N, R, F, I, P>(roots: R, adjacent_gen: F, goal_pred: P) -> BfsTree<N>
+where
+    N: Copy + Eq + Hash,
+    R: IntoIterator<Item = N>,
+    F: Fn(N) -> I,
+    I: Iterator<Item = N>,
+    P: Fn(N) -> bool,
+{
+    let mut queue = VecDeque::new();
+    let mut reached = HashMap::new();
+    for root in roots {
+        if reached.insert(root, (0, None)).is_none() {
+            queue.push_back((0, root));
+        }
+    }
+    while let Some((depth, node)) = queue.pop_front() {
+        if goal_pred(node) {
+            return BfsTree {
+                reached,
+                goal: Some(node),
+            };
+        }
+        for adj in adjacent_gen(node) {
+            if let Entry::Vacant(entry) = reached.entry(adj) {
+                entry.insert((depth + 1, Some(node)));
+                queue.push_back((depth + 1, adj));
+            }
+        }
+    }
+    BfsTree {
+        reached,
+        goal: None,
+    }
+}
+
+/// Depth-first search from `root`, calling `visitor` on each node (root
+/// included) in preorder. Uses an explicit stack, so deep graphs are fine.
+pub fn dfs<N, F, G, I>(root: N, adjacent_gen: F, mut visitor: G) -> HashSet<N>
+where
+    N: Copy + Eq + Hash,
+    F: Fn(N) -> I,
+    I: Iterator<Item = N>,
+    G: FnMut(N),
+{
+    let mut stack = vec![root];
+    let mut visited = HashSet::new();
+    while let Some(node) = stack.pop() {
+        if visited.insert(node) {
+            visitor(node);
+            let start = stack.len();
+            stack.extend(adjacent_gen(node).filter(|adj| !visited.contains(adj)));
+            // so the first neighbour is visited first
+            stack[start..].reverse();
+        }
+    }
+    visited
+}
+
+/// Label the connected components of an undirected graph, numbering them
+/// from 0 in order of the first of `nodes` in each. Nodes reachable from
+/// `nodes` are labelled too. Returns the labels and the number of components.
+pub fn connected_components<N, R, F, I>(nodes: R, adjacent_gen: F) -> (HashMap<N, usize>, usize)
+where
+    N: Copy + Eq + Hash,
+    R: IntoIterator<Item = N>,
+    F: Fn(N) -> I,
+    I: Iterator<Item = N>,
+{
+    let mut labels = HashMap::new();
+    let mut count = 0;
+    for node in nodes {
+        if labels.contains_key(&node) {
+            continue;
+        }
+        let mut stack = vec![node];
+        labels.insert(node, count);
+        while let Some(node) = stack.pop() {
+            for adj in adjacent_gen(node) {
+                if let Entry::Vacant(entry) = labels.entry(adj) {
+                    entry.insert(count);
+                    stack.push(adj);
+                }
+            }
+        }
+        count += 1;
+    }
+    (labels, count)
+}
+
+/// Order `nodes` and everything reachable from them so that each node comes
+/// before all of its successors. Fails with a node on a cycle if there is one.
+pub fn topological_sort<N, R, F, I>(nodes: R, adjacent_gen: F) -> Result<Vec<N>, N>
+where
+    N: Copy + Eq + Hash,
+    R: IntoIterator<Item = N>,
+    F: Fn(N) -> I,
+    I: Iterator<Item = N>,
+{
+    // false while a node's descendants are still being explored
+    let mut finished = HashMap::new();
+    let mut order = Vec::new();
+    for root in nodes {
+        if finished.contains_key(&root) {
+            continue;
+        }
+        finished.insert(root, false);
+        let mut stack = vec![(root, adjacent_gen(root))];
+        while let Some((node, adjs)) = stack.last_mut() {
+            match adjs.next() {
+                Some(adj) => match finished.get(&adj) {
+                    Some(false) => return Err(adj),
+                    Some(true) => {}
+                    None => {
+                        finished.insert(adj, false);
+                        stack.push((adj, adjacent_gen(adj)));
+                    }
+                },
+                None => {
+                    finished.insert(*node, true);
+                    order.push(*node);
+                    stack.pop();
+                }
+            }
+        }
+    }
+    order.reverse();
+    Ok(order)
+}
+
+/// The cheapest path found by `dijkstra` or `astar`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ShortestPath<N, C> {
+    pub cost: C,
+    /// The nodes from the root to the goal, inclusive.
+    pub nodes: Vec<N>,
+    /// The cost of each step, so `step_costs[i]` takes `nodes[i]` to `nodes[i + 1]`.
+    pub step_costs: Vec<C>,
+    /// How many nodes were expanded (had their neighbours generated).
+    pub expanded: usize,
+}
+
+pub fn dijkstra<N, C, F, I, P>(root: N, adjacent_gen: F, goal_pred: P) -> Option<ShortestPath<N, C>>
+where
+    N: Copy + Ord + Hash,
+    F: Fn(N) -> I,
+    I: Iterator<Item = (C, N)>,
+    P: Fn(N) -> bool,
+    C: Copy + Ord + Add<Output = C> + Default,
+{
+    astar(root, adjacent_gen, goal_pred, |_| C::default())
+}
+
+/// Dijkstra's algorithm guided by `heuristic`, a lower bound on the cost from
+/// a node to the goal. The path is only guaranteed to be the cheapest if the
+/// heuristic is consistent, i.e. it never drops by more than a step costs.
+pub fn astar<N, C, F, I, P, H>(
+    root: N,
+    adjacent_gen: F,
+    goal_pred: P,
+    heuristic: H,
+) -> Option<ShortestPath<N, C>>
+where
+    N: Copy + Ord + Hash,
+    F: Fn(N) -> I,
+    I: Iterator<Item = (C, N)>,
+    P: Fn(N) -> bool,
+    H: Fn(N) -> C,
+    C: Copy + Ord + Add<Output = C> + Default,
+{
+    let mut queue = BinaryHeap::new();
+    // the cheapest known cost of each node, and the step that reached it
+    let mut best = HashMap::new();
+    let mut expanded = 0;
+    queue.push((Reverse(heuristic(root)), C::default(), root));
+    best.insert(root, (C::default(), None));
+    while let Some((_, cost, node)) = queue.pop() {
+        if goal_pred(node) {
+            let (mut nodes, mut step_costs) = (vec![node], vec![]);
+            while let Some(&(_, Some((prev, step_cost)))) = best.get(nodes.last().unwrap()) {
+                nodes.push(prev);
+                step_costs.push(step_cost);
+            }
+            nodes.reverse();
+            step_costs.reverse();
+            return Some(ShortestPath {
+                cost,
+                nodes,
+                step_costs,
+                expanded,
+            });
+        } else if best.get(&node).is_some_and(|&(c, _)| c < cost) {
+            continue;
+        }
+        expanded += 1;
+        for (delta_cost, adj) in adjacent_gen(node) {
+            let adj_cost = cost + delta_cost;
+            if best.get(&adj).is_none_or(|&(c, _)| c > adj_cost) {
+                best.insert(adj, (adj_cost, Some((node, delta_cost))));
+                queue.push((Reverse(adj_cost + heuristic(adj)), adj_cost, adj));
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod test_search {
+    use super::*;
+
+    // a line of nodes 0..=10, with a cheap shortcut from 2 to 8
+    fn adjacent(n: u32) -> impl Iterator<Item = (u32, u32)> {
+        let shortcut = (n == 2).then_some((3, 8));
+        let steps = [n.checked_sub(1), (n < 10).then_some(n + 1)];
+        IntoIterator::into_iter(steps)
+            .flatten()
+            .map(|m| (2, m))
+            .chain(shortcut)
+    }
+
+    #[test]
+    fn test_search_shortest_path() {
+        let path = dijkstra(0, adjacent, |n| n == 10).unwrap();
+        assert_eq!(path.cost, 11);
+        assert_eq!(path.nodes, [0, 1, 2, 8, 9, 10]);
+        assert_eq!(path.step_costs, [2, 2, 3, 2, 2]);
+        let guided = astar(0, adjacent, |n| n == 10, |n| (10 - n) / 3).unwrap();
+        assert_eq!((guided.cost, guided.nodes), (path.cost, path.nodes));
+        assert!(guided.expanded <= path.expanded);
+        assert_eq!(dijkstra(0, adjacent, |n| n == 11), None);
+    }
+
+    #[test]
+    fn test_search_traversals() {
+        let unweighted = |n| adjacent(n).map(|(_, m)| m);
+        let tree = bfs_tree([0, 10], unweighted, |_| false);
+        assert_eq!(tree.len(), 11);
+        assert_eq!((tree.depth(5), tree.depth(7)), (Some(5), Some(3)));
+        assert_eq!(tree.path_to(6), Some(vec![10, 9, 8, 7, 6]));
+        let tree = bfs_tree([0], unweighted, |n| n == 9);
+        assert_eq!(tree.goal(), Some(9));
+        assert_eq!(tree.path_to(9), Some(vec![0, 1, 2, 8, 9]));
+
+        let mut preorder = vec![];
+        dfs(3, unweighted, |n| preorder.push(n));
+        assert_eq!(preorder, [3, 2, 1, 0, 8, 7, 6, 5, 4, 9, 10]);
+
+        let mod_five = |n: u32| IntoIterator::into_iter([(n + 5) % 20, (n + 15) % 20]);
+        let (labels, count) = connected_components(0..20, mod_five);
+        assert_eq!(count, 5);
+        assert_eq!(labels[&17], 2);
+
+        let divisors = |n: u32| (2..n).filter(move |&d| n.is_multiple_of(d));
+        let order = topological_sort([12, 30], divisors).unwrap();
+        let position = |n| order.iter().position(|&m| m == n).unwrap();
+        assert!(position(12) < position(6) && position(6) < position(3));
+        assert_eq!(order.len(), 9);
+        assert!(topological_sort([0], |n| IntoIterator::into_iter([(n + 1) % 3])).is_err());
+    }
+}
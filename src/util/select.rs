@@ -0,0 +1,194 @@
+use std::cmp::Ordering;
+
+/// The `k`th smallest element of `slice` (counting from 0), found by partly
+/// reordering it: everything before index `k` ends up no greater, and
+/// everything after it no less. Takes O(n) time even in the worst case.
+pub fn qselect<T: Ord>(k: usize, slice: &mut [T]) -> &T {
+    qselect_by(k, slice, T::cmp)
+}
+
+/// `qselect` by the order given by `compare`.
+pub fn qselect_by<T, F>(k: usize, slice: &mut [T], mut compare: F) -> &T
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    assert!(k < slice.len());
+    select(k, slice, &mut compare);
+    &slice[k]
+}
+
+/// `qselect` by the order of the keys given by `key`.
+pub fn qselect_by_key<T, K, F>(k: usize, slice: &mut [T], mut key: F) -> &T
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    qselect_by(k, slice, |a, b| key(a).cmp(&key(b)))
+}
+
+/// The median of `slice`, or the upper of the two middle elements if it has
+/// an even length.
+pub fn median<T: Ord>(slice: &mut [T]) -> &T {
+    qselect(slice.len() / 2, slice)
+}
+
+/// Quickselect with median-of-three pivots, falling back to median-of-medians
+/// pivots once it has done more partitioning than it should have needed. The
+/// partitioning is a loop; only finding a median-of-medians pivot recurses.
+fn select<T, F: FnMut(&T, &T) -> Ordering>(k: usize, slice: &mut [T], compare: &mut F) {
+    let (mut lo, mut hi) = (0, slice.len());
+    // how many more elements to partition around cheap pivots
+    let mut budget = 8 * slice.len();
+    loop {
+        let part = &mut slice[lo..hi];
+        if part.len() <= 5 {
+            insertion_sort(part, compare);
+            return;
+        }
+        let pivot = if budget >= part.len() {
+            budget -= part.len();
+            median_of_three(part, compare)
+        } else {
+            median_of_medians(part, compare)
+        };
+        let (less, greater) = partition(part, pivot, compare);
+        if k < lo + less {
+            hi = lo + less;
+        } else if k >= lo + greater {
+            lo += greater;
+        } else {
+            return;
+        }
+    }
+}
+
+fn insertion_sort<T, F: FnMut(&T, &T) -> Ordering>(slice: &mut [T], compare: &mut F) {
+    for i in 1..slice.len() {
+        let mut j = i;
+        while j > 0 && compare(&slice[j - 1], &slice[j]) == Ordering::Greater {
+            slice.swap(j - 1, j);
+            j -= 1;
+        }
+    }
+}
+
+fn median_of_three<T, F: FnMut(&T, &T) -> Ordering>(slice: &[T], compare: &mut F) -> usize {
+    let (a, b, c) = (0, slice.len() / 2, slice.len() - 1);
+    let mut less = |i: usize, j: usize| compare(&slice[i], &slice[j]) == Ordering::Less;
+    match (less(a, b), less(b, c), less(a, c)) {
+        (true, true, _) | (false, false, _) => b,
+        (true, false, true) | (false, true, false) => c,
+        _ => a,
+    }
+}
+
+/// A pivot with at least 30% of the slice on either side: the median of the
+/// medians of groups of 5. The medians are gathered at the front of the slice.
+///
+/// Selecting among the medians recurses into `select` on a fifth of the
+/// slice, so the recursion is at most log5(n) deep, under 30 frames for any
+/// slice that fits in memory. Doing it iteratively instead, by taking medians
+/// of medians again until few are left, gives a pivot that is only near the
+/// middle of the medians and loses the linear worst case.
+fn median_of_medians<T, F: FnMut(&T, &T) -> Ordering>(slice: &mut [T], compare: &mut F) -> usize {
+    let groups = slice.len() / 5;
+    for group in 0..groups {
+        insertion_sort(&mut slice[5 * group..5 * group + 5], compare);
+        slice.swap(group, 5 * group + 2);
+    }
+    select(groups / 2, &mut slice[..groups], compare);
+    groups / 2
+}
+
+/// Partition around the element at `pivot` into the elements less than it,
+/// equal to it and greater than it, returning where the middle part starts
+/// and ends.
+fn partition<T, F>(slice: &mut [T], pivot: usize, compare: &mut F) -> (usize, usize)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    // the pivot waits at the front while the rest is sorted into
+    // [less | equal | unsorted | greater]
+    slice.swap(0, pivot);
+    let (mut less, mut i, mut greater) = (1, 1, slice.len());
+    while i < greater {
+        match compare(&slice[i], &slice[0]) {
+            Ordering::Less => {
+                slice.swap(less, i);
+                less += 1;
+                i += 1;
+            }
+            Ordering::Equal => i += 1,
+            Ordering::Greater => {
+                greater -= 1;
+                slice.swap(i, greater);
+            }
+        }
+    }
+    slice.swap(0, less - 1);
+    (less - 1, greater)
+}
+
+#[cfg(test)]
+mod test_select {
+    use super::*;
+    use crate::util::Random;
+
+    #[test]
+    fn test_select_against_sorting() {
+        let mut random = Random::new(0x2545_f491);
+        for round in 0..300 {
+            let len = 1 + random.below(200);
+            let range = [2, 10, 1000][round % 3];
+            let mut values = (0..len).map(|_| random.below(range)).collect::<Vec<_>>();
+            match round % 5 {
+                0 => values.sort_unstable(),
+                1 => values.sort_unstable_by(|a, b| b.cmp(a)),
+                _ => {}
+            }
+            let mut sorted = values.clone();
+            sorted.sort_unstable();
+            let k = random.below(len);
+            let mut selected = values.clone();
+            assert_eq!(*qselect(k, &mut selected), sorted[k]);
+            assert!(selected[..k].iter().all(|&x| x <= sorted[k]));
+            assert!(selected[k + 1..].iter().all(|&x| x >= sorted[k]));
+            let by_key = *qselect_by_key(k, &mut values.clone(), |&x| std::cmp::Reverse(x));
+            assert_eq!(by_key, sorted[len - 1 - k]);
+            assert_eq!(*median(&mut values), sorted[len / 2]);
+        }
+    }
+
+    #[test]
+    fn test_select_adversary() {
+        // McIlroy's adversary, which decides how items compare only when it
+        // has to, so as to make each pivot as bad as possible
+        let len = 5000;
+        let gas = usize::MAX;
+        let (mut values, mut solid, mut candidate) = (vec![gas; len], 0, 0);
+        let mut comparisons = 0;
+        let compare = |&a: &usize, &b: &usize| {
+            comparisons += 1;
+            if values[a] == gas && values[b] == gas {
+                let frozen = if a == candidate { a } else { b };
+                values[frozen] = solid;
+                solid += 1;
+            }
+            if values[a] == gas {
+                candidate = a;
+            } else if values[b] == gas {
+                candidate = b;
+            }
+            values[a].cmp(&values[b])
+        };
+        let mut items = (0..len).collect::<Vec<_>>();
+        let k = len / 3;
+        let kth = *qselect_by(k, &mut items, compare);
+        assert!(comparisons < 40 * len, "{} comparisons", comparisons);
+        let count = |pred: fn(Ordering) -> bool| {
+            let ordering = |&item: &usize| values[item].cmp(&values[kth]);
+            items.iter().filter(|item| pred(ordering(item))).count()
+        };
+        assert!(count(Ordering::is_lt) <= k && count(Ordering::is_le) > k);
+    }
+}
@@ -0,0 +1,198 @@
+use itertools::{Itertools, Merge};
+use std::{
+    cmp::Ordering,
+    iter::{FromIterator, Peekable},
+    ops::{Bound, RangeBounds},
+    slice::Iter,
+};
+
+/// A `Vec` kept in ascending order. Build it in bulk with `collect` where
+/// possible, since `push` has to shift the elements after the new one.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct SortedVec<T>(Vec<T>);
+
+impl<T> SortedVec<T> {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self(Vec::with_capacity(capacity))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.0.iter()
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        &self.0
+    }
+
+    pub fn into_vec(self) -> Vec<T> {
+        self.0
+    }
+
+    pub fn push(&mut self, element: T)
+    where
+        T: Ord,
+    {
+        let index = self.0.binary_search(&element).unwrap_or_else(|i| i);
+        self.0.insert(index, element);
+    }
+
+    pub fn contains(&self, element: &T) -> bool
+    where
+        T: Ord,
+    {
+        self.0.binary_search(element).is_ok()
+    }
+
+    /// The elements within `range`, found by binary search.
+    pub fn range<R: RangeBounds<T>>(&self, range: R) -> &[T]
+    where
+        T: Ord,
+    {
+        let start = match range.start_bound() {
+            Bound::Included(start) => self.0.partition_point(|x| x < start),
+            Bound::Excluded(start) => self.0.partition_point(|x| x <= start),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(end) => self.0.partition_point(|x| x <= end),
+            Bound::Excluded(end) => self.0.partition_point(|x| x < end),
+            Bound::Unbounded => self.0.len(),
+        };
+        &self.0[start..end.max(start)]
+    }
+
+    pub fn dedup(&mut self)
+    where
+        T: PartialEq,
+    {
+        self.0.dedup();
+    }
+
+    /// Every element of both vectors, in order.
+    pub fn merge<'a>(&'a self, other: &'a Self) -> Merge<Iter<'a, T>, Iter<'a, T>>
+    where
+        T: PartialOrd,
+    {
+        self.iter().merge(other.iter())
+    }
+
+    /// Pairs of equal elements from the two vectors, in order. Each element
+    /// is paired at most once, so runs of equal elements pair up one to one.
+    pub fn intersection<'a>(
+        &'a self,
+        other: &'a Self,
+    ) -> Intersection<'a, T, fn(&T, &T) -> Ordering>
+    where
+        T: Ord,
+    {
+        self.intersection_by(other, T::cmp)
+    }
+
+    /// Like `intersection`, but comparing with `compare`, which must be
+    /// consistent with the order of the elements (e.g. comparing a prefix).
+    pub fn intersection_by<'a, F>(&'a self, other: &'a Self, compare: F) -> Intersection<'a, T, F>
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        Intersection {
+            lhs: self.iter().peekable(),
+            rhs: other.iter().peekable(),
+            compare,
+        }
+    }
+}
+
+impl<T> Default for SortedVec<T> {
+    fn default() -> Self {
+        Self(Vec::new())
+    }
+}
+
+impl<T: Ord> From<Vec<T>> for SortedVec<T> {
+    fn from(mut vec: Vec<T>) -> Self {
+        vec.sort_unstable();
+        Self(vec)
+    }
+}
+
+impl<T: Ord> FromIterator<T> for SortedVec<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::from(iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+impl<'a, T> IntoIterator for &'a SortedVec<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+pub struct Intersection<'a, T, F> {
+    lhs: Peekable<Iter<'a, T>>,
+    rhs: Peekable<Iter<'a, T>>,
+    compare: F,
+}
+
+impl<'a, T, F> Iterator for Intersection<'a, T, F>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    type Item = (&'a T, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (&lhs, &rhs) = (self.lhs.peek()?, self.rhs.peek()?);
+            match (self.compare)(lhs, rhs) {
+                Ordering::Less => self.lhs.next(),
+                Ordering::Greater => self.rhs.next(),
+                Ordering::Equal => {
+                    self.lhs.next();
+                    self.rhs.next();
+                    return Some((lhs, rhs));
+                }
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_sorted_vec {
+    use super::*;
+
+    #[test]
+    fn test_sorted_vec() {
+        let mut odds = (0..20).rev().step_by(2).collect::<SortedVec<_>>();
+        let thirds = vec![9, 0, 3, 18, 12, 6, 15, 3]
+            .into_iter()
+            .collect::<SortedVec<_>>();
+        assert_eq!(odds.as_slice(), [1, 3, 5, 7, 9, 11, 13, 15, 17, 19]);
+        assert!(odds.contains(&7) && !odds.contains(&8));
+        assert_eq!(odds.range(4..=11), [5, 7, 9, 11]);
+        assert_eq!(odds.range(12..), [13, 15, 17, 19]);
+        assert_eq!(odds.range(8..8), []);
+        let pairs = odds.intersection(&thirds).collect::<Vec<_>>();
+        assert_eq!(pairs, [(&3, &3), (&9, &9), (&15, &15)]);
+        let merged = odds.merge(&thirds).copied().collect::<Vec<_>>();
+        assert_eq!(merged.len(), odds.len() + thirds.len());
+        assert!(merged.windows(2).all(|w| w[0] <= w[1]));
+        let mut thirds = thirds;
+        thirds.dedup();
+        assert_eq!(thirds.as_slice(), [0, 3, 6, 9, 12, 15, 18]);
+        odds.push(4);
+        assert_eq!(odds.range(..6), [1, 3, 4, 5]);
+        let by_tens = |a: &u32, b: &u32| (a / 10).cmp(&(b / 10));
+        let tens = odds.intersection_by(&thirds, by_tens).count();
+        assert_eq!(tens, 7);
+    }
+}
@@ -0,0 +1,123 @@
+/// Disjoint sets over the elements `0..len`, merged by rank and with paths
+/// compressed, so every operation takes practically constant time.
+#[derive(Clone, Debug)]
+pub struct UnionFind {
+    parents: Vec<usize>,
+    ranks: Vec<u8>,
+    // the size of each set, kept up to date at its root
+    sizes: Vec<usize>,
+    components: usize,
+}
+
+impl UnionFind {
+    /// Each element in a set of its own.
+    pub fn new(len: usize) -> Self {
+        Self {
+            parents: (0..len).collect(),
+            ranks: vec![0; len],
+            sizes: vec![1; len],
+            components: len,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parents.is_empty()
+    }
+
+    /// How many sets there are.
+    pub fn components(&self) -> usize {
+        self.components
+    }
+
+    /// The root of the set containing `x`, which stands for the whole set.
+    pub fn find(&mut self, x: usize) -> usize {
+        let mut root = x;
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+        let mut x = x;
+        while self.parents[x] != root {
+            x = std::mem::replace(&mut self.parents[x], root);
+        }
+        root
+    }
+
+    /// Merge the sets containing `a` and `b`, returning whether they were
+    /// separate.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        let (child, root) = if self.ranks[a] < self.ranks[b] {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        self.parents[child] = root;
+        self.sizes[root] += self.sizes[child];
+        if self.ranks[child] == self.ranks[root] {
+            self.ranks[root] += 1;
+        }
+        self.components -= 1;
+        true
+    }
+
+    pub fn same(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// The size of the set containing `x`.
+    pub fn size(&mut self, x: usize) -> usize {
+        let root = self.find(x);
+        self.sizes[root]
+    }
+
+    /// The root of each set, in order.
+    pub fn roots(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.len()).filter(move |&x| self.parents[x] == x)
+    }
+}
+
+#[cfg(test)]
+mod test_union_find {
+    use super::*;
+    use crate::util::Random;
+
+    #[test]
+    fn test_union_find_against_labels() {
+        let mut random = Random::new(0x1234_5678);
+        let len = 200;
+        let mut sets = UnionFind::new(len);
+        // the naive way: relabel one whole set on every merge
+        let mut labels = (0..len).collect::<Vec<_>>();
+        for _ in 0..300 {
+            let (a, b) = (random.below(len), random.below(len));
+            let (from, to) = (labels[a], labels[b]);
+            labels
+                .iter_mut()
+                .filter(|l| **l == from)
+                .for_each(|l| *l = to);
+            assert_eq!(sets.union(a, b), from != to);
+            let (c, d) = (random.below(len), random.below(len));
+            assert_eq!(sets.same(c, d), labels[c] == labels[d]);
+            let size = labels.iter().filter(|&&l| l == labels[c]).count();
+            assert_eq!(sets.size(c), size);
+        }
+        let mut distinct = labels.clone();
+        distinct.sort_unstable();
+        distinct.dedup();
+        assert_eq!(sets.components(), distinct.len());
+        assert_eq!(sets.roots().count(), distinct.len());
+        let total = sets
+            .roots()
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|r| sets.size(r));
+        assert_eq!(total.sum::<usize>(), len);
+    }
+}
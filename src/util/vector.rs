@@ -0,0 +1,230 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    ops::{Add, AddAssign, Index, Mul, Neg, Sub, SubAssign},
+};
+
+macro_rules! vector {
+    ($name:ident, $n:literal, $($field:ident),+) => {
+        #[derive(Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+        pub struct $name<T> {
+            $(pub $field: T),+
+        }
+
+        impl<T> $name<T> {
+            pub const fn new($($field: T),+) -> Self {
+                Self { $($field),+ }
+            }
+        }
+
+        impl<T: Copy + Ord + Add<Output = T> + Neg<Output = T>> $name<T> {
+            pub fn manhattan(self) -> T {
+                let abs = |n: T| n.max(-n);
+                vector!(@fold +, $(abs(self.$field)),+)
+            }
+
+            pub fn chebyshev(self) -> T {
+                let abs = |n: T| n.max(-n);
+                vector!(@fold max, $(abs(self.$field)),+)
+            }
+        }
+
+        impl<T> From<[T; $n]> for $name<T> {
+            fn from([$($field),+]: [T; $n]) -> Self {
+                Self { $($field),+ }
+            }
+        }
+
+        impl<T> From<$name<T>> for [T; $n] {
+            fn from(v: $name<T>) -> Self {
+                [$(v.$field),+]
+            }
+        }
+
+        impl<T> Index<usize> for $name<T> {
+            type Output = T;
+
+            fn index(&self, axis: usize) -> &T {
+                [$(&self.$field),+][axis]
+            }
+        }
+
+        impl<T: Add<Output = T>> Add for $name<T> {
+            type Output = Self;
+
+            fn add(self, other: Self) -> Self {
+                Self { $($field: self.$field + other.$field),+ }
+            }
+        }
+
+        impl<T: Sub<Output = T>> Sub for $name<T> {
+            type Output = Self;
+
+            fn sub(self, other: Self) -> Self {
+                Self { $($field: self.$field - other.$field),+ }
+            }
+        }
+
+        impl<T: Neg<Output = T>> Neg for $name<T> {
+            type Output = Self;
+
+            fn neg(self) -> Self {
+                Self { $($field: -self.$field),+ }
+            }
+        }
+
+        impl<T: Copy + Mul<Output = T>> Mul<T> for $name<T> {
+            type Output = Self;
+
+            fn mul(self, scale: T) -> Self {
+                Self { $($field: self.$field * scale),+ }
+            }
+        }
+
+        impl<T: AddAssign> AddAssign for $name<T> {
+            fn add_assign(&mut self, other: Self) {
+                $(self.$field += other.$field;)+
+            }
+        }
+
+        impl<T: SubAssign> SubAssign for $name<T> {
+            fn sub_assign(&mut self, other: Self) {
+                $(self.$field -= other.$field;)+
+            }
+        }
+
+        impl<T: Display> Display for $name<T> {
+            fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+                let [first, rest @ ..] = [$(&self.$field),+];
+                write!(f, "{}", first)?;
+                rest.iter().try_for_each(|n| write!(f, ",{}", n))
+            }
+        }
+    };
+    (@fold +, $first:expr, $($rest:expr),+) => {
+        $first $(+ $rest)+
+    };
+    (@fold max, $first:expr, $($rest:expr),+) => {
+        $first $(.max($rest))+
+    };
+}
+
+vector!(Vec2, 2, x, y);
+vector!(Vec3, 3, x, y, z);
+
+/// One of the 24 rotations of a cube onto itself, i.e. a signed permutation
+/// of the axes with determinant 1. Output axis `i` is input axis `axes[i]`,
+/// negated if `negate[i]`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Rotation3 {
+    axes: [usize; 3],
+    negate: [bool; 3],
+}
+
+impl Rotation3 {
+    pub const IDENTITY: Self = Self {
+        axes: [0, 1, 2],
+        negate: [false; 3],
+    };
+
+    pub fn all() -> impl Iterator<Item = Self> {
+        const PERMUTATIONS: [([usize; 3], bool); 6] = [
+            ([0, 1, 2], false),
+            ([0, 2, 1], true),
+            ([1, 0, 2], true),
+            ([1, 2, 0], false),
+            ([2, 0, 1], false),
+            ([2, 1, 0], true),
+        ];
+        IntoIterator::into_iter(PERMUTATIONS).flat_map(|(axes, odd)| {
+            (0..8)
+                .map(move |signs: u8| Self {
+                    axes,
+                    negate: [signs & 1 != 0, signs & 2 != 0, signs & 4 != 0],
+                })
+                .filter(move |r| r.negate.iter().filter(|&&n| n).count() % 2 == odd as usize)
+        })
+    }
+
+    pub fn inverse(self) -> Self {
+        let mut inverse = Self::IDENTITY;
+        for (i, &axis) in self.axes.iter().enumerate() {
+            inverse.axes[axis] = i;
+            inverse.negate[axis] = self.negate[i];
+        }
+        inverse
+    }
+}
+
+/// Composition: `(a * b) * v == a * (b * v)`.
+impl Mul for Rotation3 {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        let mut composed = Self::IDENTITY;
+        for i in 0..3 {
+            composed.axes[i] = other.axes[self.axes[i]];
+            composed.negate[i] = self.negate[i] != other.negate[self.axes[i]];
+        }
+        composed
+    }
+}
+
+impl<T: Copy + Neg<Output = T>> Mul<Vec3<T>> for Rotation3 {
+    type Output = Vec3<T>;
+
+    fn mul(self, v: Vec3<T>) -> Vec3<T> {
+        let axis = |i: usize| {
+            let n = v[self.axes[i]];
+            if self.negate[i] {
+                -n
+            } else {
+                n
+            }
+        };
+        Vec3::new(axis(0), axis(1), axis(2))
+    }
+}
+
+#[cfg(test)]
+mod test_vector {
+    use super::*;
+    use hashbrown::HashSet;
+
+    #[test]
+    fn test_vector_arithmetic() {
+        let (a, b) = (Vec3::new(1, -2, 3), Vec3::new(4, 5, -6));
+        assert_eq!(a + b, Vec3::new(5, 3, -3));
+        assert_eq!(a - b, Vec3::new(-3, -7, 9));
+        assert_eq!(-a * 2, Vec3::new(-2, 4, -6));
+        assert_eq!((a - b).manhattan(), 19);
+        assert_eq!((a - b).chebyshev(), 9);
+        assert_eq!(Vec2::from([3, -4]).manhattan(), 7);
+        assert_eq!(b.to_string(), "4,5,-6");
+    }
+
+    #[test]
+    fn test_vector_rotations() {
+        let v = Vec3::new(1, 2, 3);
+        let rotations = Rotation3::all().collect::<Vec<_>>();
+        assert_eq!(rotations.len(), 24);
+        let images = rotations.iter().map(|&r| r * v).collect::<HashSet<_>>();
+        assert_eq!(images.len(), 24);
+        // a quarter turn about z
+        let turn = rotations
+            .iter()
+            .copied()
+            .find(|&r| {
+                r * Vec3::new(1, 0, 0) == Vec3::new(0, 1, 0)
+                    && r * Vec3::new(0, 0, 1) == Vec3::new(0, 0, 1)
+            })
+            .unwrap();
+        assert_eq!(turn * v, Vec3::new(-2, 1, 3));
+        for &a in rotations.iter() {
+            assert_eq!(a * a.inverse(), Rotation3::IDENTITY);
+            assert_eq!(a.inverse() * (a * v), v);
+            for &b in rotations.iter() {
+                assert_eq!((a * b) * v, a * (b * v));
+            }
+        }
+    }
+}
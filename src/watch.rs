@@ -0,0 +1,99 @@
+use crate::{
+    corpus::{self, Outcome},
+    day::Solver,
+};
+use hashbrown::HashMap;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The puzzle input followed by any example inputs, which are files in the
+/// cache directory named like `day9.example` or `day9.example2`.
+fn watched_inputs(day: usize) -> Vec<PathBuf> {
+    let mut inputs = vec![PathBuf::from(format!("cache/day{}.in", day))];
+    let example_prefix = format!("day{}.example", day);
+    if let Ok(entries) = fs::read_dir("cache") {
+        let mut examples = entries
+            .filter_map(Result::ok)
+            .filter(|entry| {
+                entry
+                    .file_name()
+                    .to_string_lossy()
+                    .starts_with(&example_prefix)
+            })
+            .map(|entry| entry.path())
+            .collect::<Vec<_>>();
+        examples.sort();
+        inputs.extend(examples);
+    }
+    inputs
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+fn print_answer(part: &str, answer: &str, previous: Option<&String>) {
+    match previous {
+        Some(previous) if previous == answer => println!("  {}: {} (unchanged)", part, answer),
+        Some(previous) => println!("  {}: {} (was {})", part, answer, previous),
+        None => println!("  {}: {}", part, answer),
+    }
+}
+
+/// Poll the inputs for a day and re-run the solver on any that change,
+/// printing the new answers next to the previous ones. The solver itself
+/// can't be reloaded, so changes to the day's source are only reported.
+pub fn run(day: usize, solver: Solver) -> ! {
+    let source = PathBuf::from(format!("src/day{:02}.rs", day));
+    let source_time = modified(&source);
+    let mut source_reported = false;
+    let mut seen = HashMap::new();
+    let mut previous = HashMap::<PathBuf, (String, String)>::new();
+    println!("watching day{:02} (ctrl-c to stop)", day);
+    loop {
+        for path in watched_inputs(day) {
+            let time = modified(&path);
+            if seen.get(&path) == Some(&time) {
+                continue;
+            }
+            seen.insert(path.clone(), time);
+            println!();
+            let input = match fs::read_to_string(&path) {
+                Ok(input) => input,
+                Err(_) => {
+                    println!("{}: missing", path.display());
+                    continue;
+                }
+            };
+            println!("{}:", path.display());
+            match corpus::evaluate(solver, &input, None) {
+                Outcome::Solved(solution, _) => {
+                    let answers = previous.get(&path);
+                    print_answer("part1", &solution.part1, answers.map(|a| &a.0));
+                    print_answer("part2", &solution.part2, answers.map(|a| &a.1));
+                    println!(
+                        "  (elapsed {}ms)",
+                        1000.0 * solution.total_time().as_secs_f32()
+                    );
+                    previous.insert(path, (solution.part1, solution.part2));
+                }
+                Outcome::Panicked(message) => println!("  panicked: {}", message),
+            }
+        }
+        if !source_reported && modified(&source) != source_time {
+            println!();
+            println!(
+                "{} has changed; rebuild and restart to pick up the changes",
+                source.display()
+            );
+            source_reported = true;
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}